@@ -1,3 +1,5 @@
+use std::{fmt, ops::Range};
+
 use crate::token::TokenKind;
 
 #[derive(Debug)]
@@ -11,6 +13,11 @@ impl<'a> Error<'a> {
         Self { kind, slice }
     }
 
+    /// The byte range of the error in `src`, the source the error comes from.
+    pub fn span(&self, src: &str) -> Range<usize> {
+        slice_span(src, self.slice)
+    }
+
     pub fn is_recoverable(&self) -> bool {
         !self.is_eof_error()
     }
@@ -28,10 +35,24 @@ impl<'a> Error<'a> {
     }
 }
 
+/// The byte range of `slice` in `src`, which it must be part of.
+pub fn slice_span(src: &str, slice: &str) -> Range<usize> {
+    let start = (slice.as_ptr() as usize).saturating_sub(src.as_ptr() as usize);
+    let start = start.min(src.len());
+    start..(start + slice.len()).min(src.len())
+}
+
 #[derive(Debug)]
 pub enum ErrorKind {
-    UnexpectedToken { expected: Expected, got: TokenKind },
+    UnexpectedToken {
+        expected: Expected,
+        got: TokenKind,
+    },
     Unexpected(Expected),
+    /// The source ended before the delimiter was closed.
+    Unclosed(&'static str),
+    /// A feature of the syntax that isn't implemented yet.
+    Unsupported(&'static str),
 }
 
 #[derive(Debug)]
@@ -47,3 +68,33 @@ impl From<TokenKind> for Expected {
         Expected::TokenKind(value)
     }
 }
+
+impl fmt::Display for Error<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.kind.fmt(f)
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::UnexpectedToken { expected, got } => {
+                write!(f, "expected {expected}, got {}", got.description())
+            }
+            ErrorKind::Unexpected(expected) => write!(f, "expected {expected}"),
+            ErrorKind::Unclosed(delimiter) => write!(f, "unclosed '{delimiter}'"),
+            ErrorKind::Unsupported(feature) => write!(f, "{feature} is not supported"),
+        }
+    }
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expected::TokenKind(kind) => f.write_str(kind.description()),
+            Expected::Tag(tag) => write!(f, "'{tag}'"),
+            Expected::EntryValue => f.write_str("an entry value"),
+            Expected::String => f.write_str("a string"),
+        }
+    }
+}
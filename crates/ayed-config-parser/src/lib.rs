@@ -7,7 +7,7 @@ use parser::Parser;
 mod token;
 
 mod error;
-pub use self::error::{Error, ErrorKind};
+pub use self::error::{Error, ErrorKind, slice_span};
 
 pub fn parse_module(src: &str) -> (Ast<'_>, Vec<Error<'_>>) {
     Parser::new(src).parse_module()
@@ -23,10 +23,9 @@ impl<'a> Parser<'a> {
     pub fn parse_module(mut self) -> (Ast<'a>, Vec<Error<'a>>) {
         let mut ast = Ast::default();
 
-        loop {
+        while self.peek_token().kind != TokenKind::Eof {
             match self.parse_block() {
                 Ok(block) => ast.top_level_blocks.push(block),
-                Err(err) if err.is_eof_error() => break,
                 Err(err) => {
                     let can_recover = err.is_recoverable();
                     self.add_error(err);
@@ -43,8 +42,11 @@ impl<'a> Parser<'a> {
     fn parse_block(&mut self) -> Result<Block<'a>, Error<'a>> {
         let annotations = self.parse_annotations()?;
         let is_override = annotations.iter().any(|s| s.slice == "@override");
-        if annotations.iter().any(|s| s.slice == "@raw") {
-            todo!()
+        if let Some(raw) = annotations.iter().find(|s| s.slice == "@raw") {
+            return Err(Error::new(
+                ErrorKind::Unsupported("the @raw annotation"),
+                raw.slice,
+            ));
         }
 
         let name = self.expect(TokenKind::Identifier)?;
@@ -127,10 +129,14 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_entry_name(&mut self) -> Result<Span<'a>, Error<'a>> {
+        // Only fails at the end of the source.
         let Some((i, name)) = next_entry_name(self.src) else {
             return Err(Error::new(
-                ErrorKind::Unexpected(Expected::TokenKind(TokenKind::EntryName)),
-                self.src,
+                ErrorKind::UnexpectedToken {
+                    expected: TokenKind::EntryName.into(),
+                    got: TokenKind::Eof,
+                },
+                &self.src[self.src.len()..],
             ));
         };
         self.src = i;
@@ -154,11 +160,22 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_entry_value(&mut self, in_list: bool) -> Result<Template<'a>, Error<'a>> {
-        let (mut i, _) = take_while0(is_whitespace)(self.src);
+        // Outside of lists, values end with their line.
+        let (mut i, _) = if in_list {
+            take_while0(is_whitespace)(self.src)
+        } else {
+            take_while0(|ch| is_whitespace(ch) && ch != '\n')(self.src)
+        };
         let mut parts = Vec::new();
         while let Some((j, token)) = next_token_in_entry_value(i, in_list) {
             if token.kind == TokenKind::Escape {
-                todo!("properly handle escapes");
+                return Err(Error::new(
+                    ErrorKind::UnexpectedToken {
+                        expected: Expected::EntryValue,
+                        got: token.kind,
+                    },
+                    token.slice,
+                ));
             }
 
             if token.kind == TokenKind::Delimiter && token.slice != "$\"" {
@@ -180,7 +197,7 @@ impl<'a> Parser<'a> {
         if parts.is_empty() {
             return Err(Error::new(
                 ErrorKind::Unexpected(Expected::EntryValue),
-                self.src,
+                &self.src[..0],
             ));
         }
         Ok(Template { parts })
@@ -191,7 +208,13 @@ impl<'a> Parser<'a> {
         let mut parts = Vec::new();
         while let Some((j, token)) = next_token_in_string(i) {
             if token.kind == TokenKind::Escape {
-                todo!("properly handle escapes");
+                return Err(Error::new(
+                    ErrorKind::UnexpectedToken {
+                        expected: Expected::String,
+                        got: token.kind,
+                    },
+                    token.slice,
+                ));
             }
 
             i = j;
@@ -204,7 +227,7 @@ impl<'a> Parser<'a> {
         if parts.is_empty() {
             return Err(Error::new(
                 ErrorKind::Unexpected(Expected::String),
-                self.src,
+                &self.src[..0],
             ));
         }
         Ok(Template { parts })
@@ -225,22 +248,31 @@ impl<'a> Parser<'a> {
         close: &'static str,
         sep: Option<&'static str>,
     ) -> Result<Vec<T>, Error<'a>> {
-        self.expect_delimiter(open)?;
+        let open_token = self.expect_delimiter(open)?;
 
         let mut items = Vec::new();
         'goto_end: {
             'looop: loop {
+                // Empty lists.
+                if items.is_empty() && self.peek_token().slice == close {
+                    break 'looop;
+                }
                 match parse_fn(self) {
                     Ok(item) => {
                         items.push(item);
                     }
                     Err(err) => {
-                        let can_recover = err.is_recoverable();
-                        self.add_error(err);
-                        if !can_recover {
+                        if !err.is_recoverable() {
+                            self.add_error(Error::new(ErrorKind::Unclosed(open), open_token.slice));
                             break 'goto_end;
                         }
+                        self.add_error(err);
                         self.recover_delimited(open, close, 1);
+                        // The list was never closed, parsing it again wouldn't progress.
+                        if self.peek_token().kind == TokenKind::Eof {
+                            self.add_error(Error::new(ErrorKind::Unclosed(open), open_token.slice));
+                            break 'goto_end;
+                        }
                     }
                 }
 
@@ -341,3 +373,78 @@ impl<'a> Parser<'a> {
         token
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_module;
+
+    #[test]
+    fn parses_empty_lists() {
+        let (ast, errors) = parse_module("hooks {}\nsyntax {\n}\n");
+        assert!(errors.is_empty());
+        assert_eq!(ast.top_level_blocks.len(), 2);
+        for block in &ast.top_level_blocks {
+            let BlockKind::Mapping(mapping) = &block.kind else {
+                panic!("expected a mapping block");
+            };
+            assert!(mapping.entries.is_empty());
+        }
+
+        let (ast, errors) = parse_module("editor {\n    a $[ ]\n}\n");
+        assert!(errors.is_empty());
+        let BlockKind::Mapping(mapping) = &ast.top_level_blocks[0].kind else {
+            panic!("expected a mapping block");
+        };
+        assert!(mapping.entries[0].values.is_empty());
+    }
+
+    #[test]
+    fn reports_unclosed_lists() {
+        let src = "editor {\n    a b\n";
+        let (_, errors) = parse_module(src);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].kind, ErrorKind::Unclosed("{")));
+        assert_eq!(errors[0].span(src), 7..8);
+
+        let (_, errors) = parse_module("editor {\n    a $[ b ; c\n}\n");
+        assert!(matches!(
+            errors[0].kind,
+            ErrorKind::Unexpected(Expected::Tag("]"))
+        ));
+    }
+
+    #[test]
+    fn reports_valueless_entries() {
+        let src = "editor {\n    indent-size\n}\n";
+        let (_, errors) = parse_module(src);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0].kind,
+            ErrorKind::Unexpected(Expected::EntryValue)
+        ));
+        assert_eq!(errors[0].span(src).start, src.find("\n}").unwrap());
+    }
+
+    #[test]
+    fn reports_unsupported_syntax() {
+        let src = "editor {\n    a $x\n}\n";
+        let (_, errors) = parse_module(src);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].slice, "$x");
+
+        let (_, errors) = parse_module("editor {\n    a $\"b $x\"\n}\n");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0].kind,
+            ErrorKind::UnexpectedToken {
+                expected: Expected::String,
+                got: TokenKind::Escape,
+            }
+        ));
+
+        let (_, errors) = parse_module("@raw editor {\n    a b\n}\n");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].kind, ErrorKind::Unsupported(_)));
+    }
+}
@@ -15,6 +15,20 @@ pub enum TokenKind {
     Eof,
 }
 
+impl TokenKind {
+    pub fn description(&self) -> &'static str {
+        match self {
+            TokenKind::Identifier => "an identifier",
+            TokenKind::Delimiter => "a delimiter",
+            TokenKind::EntryName => "an entry name",
+            TokenKind::Escape => "an escape sequence",
+            TokenKind::Verbatim => "a value",
+            TokenKind::Invalid => "an invalid token",
+            TokenKind::Eof => "the end of the file",
+        }
+    }
+}
+
 pub fn next_token<'a>(mut i: &'a str) -> (&'a str, Token<'a>) {
     // The loop only exist to allow skipping comments.
     loop {
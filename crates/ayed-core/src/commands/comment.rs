@@ -0,0 +1,255 @@
+use crate::{
+    command::{CommandRegistry, helpers::focused_buffer_command, options::Options},
+    position::{Column, Position, Row},
+    selection::Selection,
+    state::TextBuffer,
+    utils::string_utils::ops::is_whitespace,
+};
+
+pub fn register_comment_commands(cr: &mut CommandRegistry) {
+    cr.register(
+        "comment-toggle",
+        focused_buffer_command(|opt, ctx| {
+            let opts = Options::new().flag("block").parse(opt)?;
+            let tokens = ctx.state.config.get_comment();
+            let style = match (&tokens.line, &tokens.block) {
+                (_, Some((start, end))) if opts.contains("block") => {
+                    CommentStyle::Block(start, end)
+                }
+                (Some(prefix), _) => CommentStyle::Line(prefix),
+                (None, Some((start, end))) => CommentStyle::Block(start, end),
+                (None, None) => return Err("no comment tokens for this file".to_string()),
+            };
+
+            // Overlapping selections would toggle the same lines twice.
+            let mut row_ranges = ctx
+                .selections
+                .iter()
+                .map(|sel| (sel.start().row, sel.end().row))
+                .collect::<Vec<_>>();
+            row_ranges.sort();
+            let mut merged_row_ranges: Vec<(Row, Row)> = Vec::new();
+            for (first, last) in row_ranges {
+                match merged_row_ranges.last_mut() {
+                    Some((_, prev_last)) if first <= *prev_last => {
+                        *prev_last = (*prev_last).max(last);
+                    }
+                    _ => merged_row_ranges.push((first, last)),
+                }
+            }
+
+            // From the end of the buffer, so that the rows yet to be toggled don't move.
+            for &(first, last) in merged_row_ranges.iter().rev() {
+                match style {
+                    CommentStyle::Line(prefix) => {
+                        toggle_line_comments(ctx.buffer, first, last, prefix)?
+                    }
+                    CommentStyle::Block(start, end) => {
+                        toggle_block_comment(ctx.buffer, first, last, start, end)?
+                    }
+                }
+            }
+
+            ctx.queue.emit("buffer-modified", "");
+            ctx.queue.emit("selections-modified", "");
+
+            Ok(())
+        }),
+    );
+}
+
+enum CommentStyle<'a> {
+    Line(&'a str),
+    Block(&'a str, &'a str),
+}
+
+/// Comments the non-blank lines of the range at their common indentation, or
+/// uncomments them if they are all already commented.
+fn toggle_line_comments(
+    buffer: &mut TextBuffer,
+    first: Row,
+    last: Row,
+    prefix: &str,
+) -> Result<(), String> {
+    let rows = (first..=last)
+        .filter(|&row| buffer.line(row).is_some_and(|line| !is_blank(line)))
+        .collect::<Vec<_>>();
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    let all_commented = rows.iter().all(|&row| {
+        let line = buffer.line(row).unwrap();
+        line[indent_len(line)..].starts_with(prefix)
+    });
+
+    if all_commented {
+        for &row in &rows {
+            let line = buffer.line(row).unwrap();
+            let indent = &line[..indent_len(line)];
+            let after_prefix = &line[indent.len() + prefix.len()..];
+            let mut removed_count = prefix.chars().count();
+            if after_prefix.starts_with(' ') {
+                removed_count += 1;
+            }
+            let start = Position::new(indent.chars().count() as Column, row);
+            delete_chars(buffer, start, removed_count)?;
+        }
+    } else {
+        let common_indent = rows
+            .iter()
+            .map(|&row| {
+                let line = buffer.line(row).unwrap();
+                line[..indent_len(line)].chars().count()
+            })
+            .min()
+            .unwrap_or(0);
+        for &row in &rows {
+            let at = Position::new(common_indent as Column, row);
+            buffer.insert_str_at(at, &format!("{prefix} "))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Wraps the lines of the range in a block comment, from the indentation of
+/// the first non-blank line, or unwraps them if they already are.
+fn toggle_block_comment(
+    buffer: &mut TextBuffer,
+    first: Row,
+    last: Row,
+    start: &str,
+    end: &str,
+) -> Result<(), String> {
+    let Some(first) = (first..=last).find(|&row| buffer.line(row).is_some_and(|l| !is_blank(l)))
+    else {
+        return Ok(());
+    };
+    let last = (first..=last)
+        .rev()
+        .find(|&row| buffer.line(row).is_some_and(|l| !is_blank(l)))
+        .unwrap_or(first);
+
+    let first_line = buffer.line(first).unwrap();
+    let first_indent = first_line[..indent_len(first_line)].chars().count() as Column;
+    let last_line = buffer.line(last).unwrap();
+    let last_content = last_line.trim_end();
+
+    // On a single line, the delimiters must not overlap (ex: `/*/`).
+    let is_commented = first_line.trim_start().starts_with(start)
+        && last_content.ends_with(end)
+        && (first != last || last_content.trim_start().len() >= start.len() + end.len());
+    if is_commented {
+        // Closing delimiter first, so that the opening one doesn't move.
+        let mut end_count = end.chars().count();
+        let mut end_column = last_content.chars().count() - end_count;
+        if last_content[..last_content.len() - end.len()].ends_with(' ') {
+            end_column -= 1;
+            end_count += 1;
+        }
+        delete_chars(buffer, Position::new(end_column as Column, last), end_count)?;
+
+        let first_line = buffer.line(first).unwrap();
+        let after_start = &first_line[indent_len(first_line) + start.len()..];
+        let mut start_count = start.chars().count();
+        if after_start.starts_with(' ') {
+            start_count += 1;
+        }
+        delete_chars(buffer, Position::new(first_indent, first), start_count)?;
+    } else {
+        let end_column = last_content.chars().count() as Column;
+        buffer.insert_str_at(Position::new(end_column, last), &format!(" {end}"))?;
+        buffer.insert_str_at(Position::new(first_indent, first), &format!("{start} "))?;
+    }
+
+    Ok(())
+}
+
+fn delete_chars(buffer: &mut TextBuffer, start: Position, count: usize) -> Result<(), String> {
+    if count == 0 {
+        return Ok(());
+    }
+    let end = start.offset((count as Column - 1, 0));
+    buffer.delete_selection(&Selection::new().with_anchor(start).with_cursor(end))
+}
+
+/// Byte length of the indentation of the line, as for `move-to-edge line-past-indent`.
+fn indent_len(line: &str) -> usize {
+    line.find(|c| !is_whitespace(c)).unwrap_or(line.len())
+}
+
+fn is_blank(line: &str) -> bool {
+    line.chars().all(is_whitespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::test_utils::TestEditor;
+
+    /// Toggles the comments of the selected lines, checks the result and that
+    /// toggling them again restores `content`.
+    fn toggle(path: &str, content: &str, selections: &str, command: &str) -> String {
+        let mut editor = TestEditor::new(path, content, selections);
+        editor.run(command).unwrap();
+        let toggled = editor.text();
+        editor.set_selections(selections);
+        editor.run(command).unwrap();
+        assert_eq!(editor.text(), content);
+        toggled
+    }
+
+    #[test]
+    fn toggles_line_comments() {
+        let content = "fn a() {\n    b();\n}";
+        let toggled = toggle("a.rs", content, "0:0-2:0", "comment-toggle");
+        assert_eq!(toggled, "// fn a() {\n//     b();\n// }");
+
+        // At the common indentation of the lines.
+        let toggled = toggle("a.rs", "    a\n  b", "0:0-1:0", "comment-toggle");
+        assert_eq!(toggled, "  //   a\n  // b");
+
+        let toggled = toggle("a.py", "a\nb", "1:0-1:0", "comment-toggle");
+        assert_eq!(toggled, "a\n# b");
+    }
+
+    #[test]
+    fn comments_mixed_regions() {
+        let toggled = toggle("a.rs", "// a\nb", "0:0-1:0", "comment-toggle");
+        assert_eq!(toggled, "// // a\n// b");
+    }
+
+    #[test]
+    fn skips_blank_lines() {
+        let toggled = toggle("a.rs", "a\n\n  \nb", "0:0-3:0", "comment-toggle");
+        assert_eq!(toggled, "// a\n\n  \n// b");
+
+        let toggled = toggle("a.rs", "\n  a\n  b\n", "0:0-3:0", "comment-toggle --block");
+        assert_eq!(toggled, "\n  /* a\n  b */\n");
+    }
+
+    #[test]
+    fn toggles_block_comments() {
+        let toggled = toggle("a.rs", "a\nb", "0:0-1:0", "comment-toggle --block");
+        assert_eq!(toggled, "/* a\nb */");
+
+        let toggled = toggle("a.rs", "    x;", "0:4-0:4", "comment-toggle --block");
+        assert_eq!(toggled, "    /* x; */");
+
+        // Without block comments, line comments are used.
+        let toggled = toggle("a.py", "a", "0:0-0:0", "comment-toggle --block");
+        assert_eq!(toggled, "# a");
+    }
+
+    #[test]
+    fn toggles_overlapping_selections_once() {
+        let toggled = toggle("a.rs", "a\nb\nc", "0:0-1:0 1:0-2:0", "comment-toggle");
+        assert_eq!(toggled, "// a\n// b\n// c");
+    }
+
+    #[test]
+    fn fails_without_comment_tokens() {
+        let mut editor = TestEditor::new("a.txt", "a", "0:0-0:0");
+        assert!(editor.run("comment-toggle").is_err());
+    }
+}
@@ -1,4 +1,13 @@
-use crate::{command::CommandRegistry, input::Input, state::regex_syntax_highlight};
+use std::path::Path;
+
+use crate::{
+    command::CommandRegistry,
+    config::{ConfigError, ConfigState},
+    input::Input,
+    position::Position,
+    selection::Selections,
+    state::{View, regex_syntax_highlight},
+};
 
 pub fn register_config_commands(cr: &mut CommandRegistry) {
     cr.register("map-input", |opt, ctx| {
@@ -51,4 +60,92 @@ pub fn register_config_commands(cr: &mut CommandRegistry) {
         ctx.state.highlights.insert(buffer_handle, highlights);
         Ok(())
     });
+
+    cr.register("config-reload", |_opt, ctx| {
+        ctx.state.config_errors = ctx.state.config.reload();
+        ctx.queue.push("generate-highlights");
+        // The files that loaded are applied even if others failed.
+        match ConfigError::summary(&ctx.state.config_errors) {
+            Some(err_msg) => ctx.state.modeline.set_error(err_msg, &ctx.state.config),
+            None => ctx
+                .state
+                .modeline
+                .set_message("config reloaded".to_string()),
+        }
+        Ok(())
+    });
+
+    cr.register("config-eval", |opt, ctx| {
+        if let Err(err) = ctx.state.config.add_module(opt) {
+            let err_msg = err.to_string();
+            ctx.state.config_errors = vec![err];
+            return Err(err_msg);
+        }
+        ctx.queue.push("generate-highlights");
+        Ok(())
+    });
+
+    cr.register("config-errors", |_opt, ctx| {
+        if ctx.state.config_errors.is_empty() {
+            ctx.state
+                .modeline
+                .set_message("no config errors".to_string());
+            return Ok(());
+        }
+        let report = ctx
+            .state
+            .config_errors
+            .iter()
+            .map(ConfigError::report)
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let (buffer_handle, is_new) = match ctx.state.config_errors_buffer {
+            Some(handle) if ctx.resources.buffers.contains(handle) => (handle, false),
+            _ => {
+                let handle = ctx.resources.open_scratch();
+                ctx.state.config_errors_buffer = Some(handle);
+                (handle, true)
+            }
+        };
+        let buffer = ctx.resources.buffers.get_mut(buffer_handle);
+        // Not an edit, the buffer isn't dirty afterwards.
+        buffer.lines = report.trim_end().split('\n').map(str::to_string).collect();
+        for selections in buffer.selections.values_mut() {
+            *selections = Selections::new();
+        }
+
+        let view_handle = match ctx.resources.view_with_buffer(buffer_handle) {
+            Some(handle) => handle,
+            None => {
+                let view = ctx.resources.views.insert(View {
+                    top_left: Position::ZERO,
+                    buffer: buffer_handle,
+                });
+                ctx.resources
+                    .buffers
+                    .get_mut(buffer_handle)
+                    .add_view_selections(view, Selections::new());
+                view
+            }
+        };
+        ctx.state.set_active_editor_view(view_handle, ctx.resources);
+        ctx.queue.set_state(ConfigState::FILE, "");
+
+        if is_new {
+            ctx.queue.emit("buffer-opened", "");
+        }
+        ctx.queue.push("generate-highlights");
+
+        Ok(())
+    });
+
+    // Hooked to `buffer-written`, reloads the config when one of its files
+    // is written.
+    cr.register("__config-reload-on-write-shim", |opt, ctx| {
+        if ctx.state.config.has_module_file(Path::new(opt)) {
+            ctx.queue.push("config-reload");
+        }
+        Ok(())
+    });
 }
@@ -89,7 +89,7 @@ pub fn register_core_commands(cr: &mut CommandRegistry) {
             "editor" => {
                 ctx.state.focused_panel = FocusedPanel::Editor;
             }
-            "modeline" => {
+            "modeline" | "search" | "goto" => {
                 let buffer = ctx.resources.buffers.insert(TextBuffer::new_empty());
                 let view = ctx.resources.views.insert(View {
                     top_left: Position::ZERO,
@@ -119,6 +119,9 @@ pub fn register_core_commands(cr: &mut CommandRegistry) {
                 // It would avoid having to cleanup and recreate them (but would still need to clear the buffer).
                 ctx.state.focused_panel = FocusedPanel::FilePicker(view);
             }
+            "marks-picker" => {
+                ctx.state.focused_panel = FocusedPanel::MarksPicker;
+            }
             "warpdrive" => {
                 ctx.state.focused_panel = FocusedPanel::Warpdrive;
             }
@@ -1,26 +1,39 @@
-use std::{collections::BTreeSet, path::Path};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
 
 use regex::Regex;
 
 use crate::{
     command::{
-        CommandRegistry,
+        CommandRegistry, ExecuteCommandContext,
         helpers::{ErrorExt, alias, focused_buffer_command},
         options::Options,
     },
-    config::ConfigState,
-    position::{Column, Offset, Position},
+    config::{ConfigState, EditorConfig, IndentRules},
+    panels::FocusedPanel,
+    position::{Column, Offset, Position, Row},
     selection::{Selection, Selections},
-    state::View,
+    slotmap::Handle,
+    state::{
+        BufferSettings, DetectedIndentation, TextBuffer, View, WordKind, WordMotion,
+        detect_indentation, find_bracket_pair, word_motion_selection,
+    },
+    ui::Rect,
     utils::{
         path_ext::PathExt,
         string_utils::{
-            byte_index_to_char_index, char_count,
+            byte_index_to_char_index,
+            case::{Case, convert_case},
+            char_count,
             ops::{is_whitespace, take_while},
         },
     },
 };
 
+use super::jump::record_active_editor_jump;
+
 pub fn register_editor_commands(cr: &mut CommandRegistry) {
     cr.register(
         "buffer-write",
@@ -33,15 +46,32 @@ pub fn register_editor_commands(cr: &mut CommandRegistry) {
             };
 
             if let Some(path) = path {
+                // The `.editorconfig` files of the new path apply from now on.
+                let detected_indentation = ctx.buffer.settings.detected_indentation;
+                ctx.buffer.settings = BufferSettings::from_editorconfig(&path);
+                ctx.buffer.settings.detected_indentation = detected_indentation;
                 ctx.buffer.set_path(path);
             }
+            let path = ctx.buffer.path().ok_or("missing path")?.to_str_or_err()?;
 
+            // Hooks of 'buffer-before-write' run before the buffer is written,
+            // and may modify it.
+            ctx.queue.emit("buffer-before-write", path);
+            ctx.queue.push("__buffer-write-now");
+
+            Ok(())
+        }),
+    );
+    cr.register(
+        "__buffer-write-now",
+        focused_buffer_command(|_opt, ctx| {
             ctx.buffer.write()?;
 
             if let Some(path) = ctx.buffer.path() {
                 let denormalized_path = ctx.state.denormalize_path(path);
                 ctx.queue
                     .push(format!("message written to {denormalized_path:?}",));
+                ctx.queue.emit("buffer-written", path.to_str_or_err()?);
             }
 
             Ok(())
@@ -72,7 +102,9 @@ pub fn register_editor_commands(cr: &mut CommandRegistry) {
             .to_string();
 
         // Cleanup buffer resource
-        ctx.resources.buffers.remove(buffer_handle);
+        let buffer = ctx.resources.buffers.remove(buffer_handle);
+        ctx.state
+            .save_closed_buffer_selections(buffer_handle, &buffer);
         // TODO clean other buffer related stuff like highlights and
         // change history.
 
@@ -99,7 +131,7 @@ pub fn register_editor_commands(cr: &mut CommandRegistry) {
     });
 
     cr.register("edit", |opt, ctx| {
-        let opts = Options::new().flag("scratch").parse(opt)?;
+        let opts = Options::new().flag("scratch").flag("no-jump").parse(opt)?;
         let scratch = opts.contains("scratch");
         let path = if opts.remainder().is_empty() {
             "".into()
@@ -121,6 +153,9 @@ pub fn register_editor_commands(cr: &mut CommandRegistry) {
                 None => {
                     buffer_handle = ctx.resources.open_file_or_scratch(&path)?;
                     buffer_opened_path = Some(&path);
+                    let buffer = ctx.resources.buffers.get_mut(buffer_handle);
+                    buffer.settings = BufferSettings::from_editorconfig(&path);
+                    buffer.settings.detected_indentation = detect_indentation(&buffer.lines);
                 }
             }
         }
@@ -142,7 +177,15 @@ pub fn register_editor_commands(cr: &mut CommandRegistry) {
             }
         };
 
-        ctx.state.active_editor_view = Some(view_handle);
+        // Switching to another buffer is a jump.
+        if !opts.contains("no-jump")
+            && let Some(jump) = ctx.state.active_editor_jump(ctx.resources)
+            && jump.buffer != buffer_handle
+        {
+            ctx.state.jump_list.record(jump, ctx.resources);
+        }
+
+        ctx.state.set_active_editor_view(view_handle, ctx.resources);
 
         // The state must be updated before 'buffer-opened' is emitted so that
         // hooked commands may behave correctly.
@@ -355,6 +398,105 @@ pub fn register_editor_commands(cr: &mut CommandRegistry) {
         }),
     );
 
+    let goto = focused_buffer_command(|opt, ctx| {
+        let (target, anchored) = parse_goto_options(opt)?;
+
+        // Only relative moves keep the extra selections.
+        let mut selections = match target {
+            GotoTarget::Relative(_) => ctx.selections.clone(),
+            _ => Selections::new_with(ctx.selections.primary(), &[]),
+        };
+        for selection in selections.iter_mut() {
+            let cursor = target.position(ctx.buffer, selection.cursor);
+            let cursor = ctx.buffer.limit_position_to_content(cursor);
+            let sel = selection.with_cursor(cursor);
+            *selection = if anchored {
+                sel
+            } else {
+                sel.with_anchor(cursor)
+            };
+        }
+
+        // Centered on the target when it is off-screen, rather than on an edge.
+        let cursor = selections.primary().cursor;
+        let view_rect =
+            Rect::with_position_and_size(ctx.view.top_left, ctx.state.editor_rect.size());
+        if !view_rect.contains_position(cursor) {
+            let half_height = view_rect.height as Row / 2;
+            ctx.view.top_left.row = (cursor.row - half_height).max(0);
+        }
+
+        *ctx.buffer.view_selections_mut(ctx.view_handle).unwrap() = selections;
+
+        ctx.queue.emit("selections-modified", "");
+
+        Ok(())
+    });
+
+    cr.register("goto", move |opt, mut ctx| {
+        // Going to a line is a jump.
+        parse_goto_options(opt)?;
+        if ctx.state.focused_view() == ctx.state.active_editor_view {
+            record_active_editor_jump(&mut ctx);
+        }
+        goto(opt, ctx)
+    });
+
+    cr.register("goto-confirm", |_opt, ctx| {
+        let FocusedPanel::Modeline(prompt_view_handle) = ctx.state.focused_panel else {
+            return Err("goto prompt not focused".to_string());
+        };
+        let prompt_buffer_handle = ctx.resources.views.get(prompt_view_handle).buffer;
+        let target = ctx
+            .resources
+            .buffers
+            .get(prompt_buffer_handle)
+            .first_line()
+            .trim()
+            .to_string();
+
+        ctx.queue.push("panel-focus editor");
+        if !target.is_empty() {
+            ctx.queue.push(format!("goto {target}"));
+        }
+
+        Ok(())
+    });
+
+    cr.register(
+        "move-to-match",
+        focused_buffer_command(|opt, mut ctx| {
+            let opts = Options::new().flag("anchored").parse(opt)?;
+            let anchored = opts.contains("anchored");
+
+            let brackets = ctx.state.config.get_brackets();
+            let highlights = ctx.state.highlights.get(&ctx.buffer_handle);
+            for selection in ctx.selections.iter_mut() {
+                let Some((_, matching)) = find_bracket_pair(
+                    ctx.buffer,
+                    selection.cursor,
+                    brackets,
+                    highlights.map(Vec::as_slice),
+                ) else {
+                    continue;
+                };
+
+                *selection = if anchored {
+                    selection.with_cursor(matching)
+                } else {
+                    selection.with_anchor(matching).with_cursor(matching)
+                };
+            }
+
+            let sels = ctx.buffer.view_selections_mut(ctx.view_handle).unwrap();
+            *sels = ctx.selections;
+
+            ctx.queue.emit("selections-modified", "");
+
+            Ok(())
+        }),
+    );
+
     cr.register("move-regex", |opt, ctx| {
         let opts = Options::new()
             .flag("reversed")
@@ -466,6 +608,49 @@ pub fn register_editor_commands(cr: &mut CommandRegistry) {
         Ok(())
     });
 
+    for (name, kind) in [
+        ("move-word", WordKind::Word),
+        ("move-subword", WordKind::Subword),
+        ("move-WORD", WordKind::BigWord),
+    ] {
+        cr.register(
+            name,
+            focused_buffer_command(move |opt, ctx| {
+                let opts = Options::new()
+                    .flag("backward")
+                    .flag("end")
+                    .flag("anchored")
+                    .parse(opt)?;
+                let motion = match (opts.contains("backward"), opts.contains("end")) {
+                    (false, false) => WordMotion::NextStart,
+                    (false, true) => WordMotion::NextEnd,
+                    (true, false) => WordMotion::PreviousStart,
+                    (true, true) => return Err("--backward and --end are exclusive".to_string()),
+                };
+                let anchored = opts.contains("anchored");
+
+                let mut selections = ctx.selections.clone();
+                for selection in selections.iter_mut() {
+                    let Some(target) =
+                        word_motion_selection(ctx.buffer, selection.cursor, kind, motion)
+                    else {
+                        continue;
+                    };
+                    *selection = if anchored {
+                        selection.with_cursor(target.cursor)
+                    } else {
+                        target
+                    };
+                }
+                *ctx.buffer.view_selections_mut(ctx.view_handle).unwrap() = selections;
+
+                ctx.queue.emit("selections-modified", "");
+
+                Ok(())
+            }),
+        );
+    }
+
     cr.register(
         "select-regex",
         focused_buffer_command(|opt, ctx| {
@@ -651,7 +836,7 @@ pub fn register_editor_commands(cr: &mut CommandRegistry) {
                 .flag("less")
                 .flag("reindent")
                 .flag("auto")
-                .flag("auto-dedent") // To be used with auto, to check whether to dedent or no considering the current line
+                .flag("auto-dedent") // To be used with auto, to only reindent lines that the indent rules dedent
                 .parse(opt)?;
             let mut more = opts.contains("more");
             let less = opts.contains("less");
@@ -669,34 +854,56 @@ pub fn register_editor_commands(cr: &mut CommandRegistry) {
                 }
             }
 
-            let indent_size = ctx.state.config.get_editor().indent_size;
+            let editor = ctx.buffer.editor_config(&ctx.state.config);
+            let indent_size = editor.indent_size.max(1);
+            let indent_rules = ctx.state.config.get_indent_rules();
+            let mut modified = false;
+            // From the top, so that reindented lines are the reference for the next ones.
             for row in affected_lines {
                 let Some(line) = ctx.buffer.line(row) else { continue };
 
-                let mut level_mod = (more as i32) - (less as i32);
-
-                if auto && auto_dedent {
-                    if line.trim() == "}" {
-                        level_mod -= 1;
-                    } else {
-                        continue;
-                    }
-                }
-
                 let indentation = take_while(line, is_whitespace).0;
                 let indent_char_count = char_count(indentation) as i32;
-                let new_indent_char_count = if auto {
-                    let Some(prev_line) = ctx.buffer.line(row - 1) else { continue };
-                    if let Some('{') = prev_line.trim_end().chars().last() {
-                        level_mod += 1;
+                let indent_width = editor.indentation_width(indentation);
+                let new_indent_width = if auto || reindent {
+                    if indent_rules.keeps(line) || (reindent && line.trim().is_empty()) {
+                        continue;
                     }
-                    char_count(take_while(prev_line, is_whitespace).0) as i32
+                    // Text moved to the new line, like a `}` right after the cursor,
+                    // stays indented until typing on that line dedents it.
+                    let dedent = if !auto_dedent {
+                        reindent
+                    } else if indent_rules.decreases(line) {
+                        true
+                    } else {
+                        // The line stopped being dedented by the last typed
+                        // character, like the `w` of `elsewhere` after `else`.
+                        let undedented =
+                            indent_from_rules(ctx.buffer, row, indent_rules, &editor, false);
+                        let was_dedented = undedented > 0
+                            && indent_width == undedented - indent_size
+                            && ctx.selections.iter().any(|sel| {
+                                sel.cursor.row == row
+                                    && decreased_before_typing(
+                                        line,
+                                        sel.cursor.column,
+                                        indent_rules,
+                                    )
+                            });
+                        if !was_dedented {
+                            continue;
+                        }
+                        false
+                    };
+                    indent_from_rules(ctx.buffer, row, indent_rules, &editor, dedent)
                 } else {
-                    indent_char_count
+                    let level_mod = (more as i32) - (less as i32);
+                    i32::max(indent_width / indent_size + level_mod, 0) * indent_size
                 };
-                let new_indent_level = i32::max(new_indent_char_count / indent_size + level_mod, 0);
-                let new_indent_char_count = new_indent_level * indent_size;
-                let new_indentation = " ".repeat(new_indent_char_count as usize);
+                let new_indentation = editor.indentation(new_indent_width);
+                if new_indentation == indentation {
+                    continue;
+                }
 
                 if indent_char_count > 0 {
                     let indent_sel = Selection::new()
@@ -707,25 +914,212 @@ pub fn register_editor_commands(cr: &mut CommandRegistry) {
 
                 ctx.buffer
                     .insert_str_at(Position::new(0, row), &new_indentation)?;
+                modified = true;
             }
 
-            ctx.queue.emit("buffer-modified", "");
-            ctx.queue.emit("selections-modified", "");
+            if modified {
+                ctx.queue.emit("buffer-modified", "");
+                ctx.queue.emit("selections-modified", "");
+            }
 
             Ok(())
         }),
     );
 
-    cr.register("__auto-indent-shim", |opt, ctx| {
-        match opt {
+    cr.register(
+        "indent-detect",
+        focused_buffer_command(|_opt, ctx| {
+            let detected = detect_indentation(&ctx.buffer.lines);
+            ctx.buffer.settings.detected_indentation = detected;
+
+            let message = match detected {
+                Some(DetectedIndentation {
+                    with_tabs: true, ..
+                }) => "detected indentation with tabs".to_string(),
+                Some(DetectedIndentation {
+                    size: Some(size), ..
+                }) => format!("detected indentation with {size} spaces"),
+                _ => "couldn't detect indentation".to_string(),
+            };
+            ctx.state.modeline.set_message(message);
+
+            Ok(())
+        }),
+    );
+
+    cr.register(
+        "__auto-indent-shim",
+        focused_buffer_command(|opt, ctx| {
             // TODO find a way to not have to check for both of these (like avoid the raw string)
-            "\n" | r"\n" => ctx.queue.push("indent --auto"),
-            // FIXME find a proper and cleaner way to handle nesting aware auto-indent/dedent
-            "}" => ctx.queue.push("indent --auto --auto-dedent"),
-            _ => (),
-        }
-        Ok(())
-    });
+            if matches!(opt, "\n" | r"\n") {
+                ctx.queue.push("indent --auto");
+                return Ok(());
+            }
+
+            // Typing may complete a line that is dedented by the indent rules,
+            // like `}`, or stop it from being dedented, like the `w` of
+            // `elsewhere`. Otherwise, the indentation is left alone.
+            let rules = ctx.state.config.get_indent_rules();
+            let changes_dedent = ctx.selections.iter().any(|sel| {
+                ctx.buffer.line(sel.cursor.row).is_some_and(|line| {
+                    rules.decreases(line) != decreased_before_typing(line, sel.cursor.column, rules)
+                })
+            });
+            if changes_dedent {
+                ctx.queue.push("indent --auto --auto-dedent");
+            }
+            Ok(())
+        }),
+    );
+
+    cr.register(
+        "__auto-pairs-shim",
+        focused_buffer_command(|opt, ctx| {
+            // Prompts are left alone.
+            if ctx.state.focused_panel != FocusedPanel::Editor {
+                return Ok(());
+            }
+            let mut chars = opt.chars();
+            let (Some(typed), None) = (chars.next(), chars.next()) else {
+                return Ok(());
+            };
+            let pairs = ctx.state.config.get_pairs();
+            let is_closer = |ch: char| pairs.iter().any(|&(_, close)| close == ch);
+            let opening_pair = pairs.iter().find(|&&(open, _)| open == typed).copied();
+            if opening_pair.is_none() && !is_closer(typed) {
+                return Ok(());
+            }
+
+            let mut modified = false;
+            for sel_idx in 0..ctx.selections.count() {
+                let sel = ctx
+                    .buffer
+                    .view_selections(ctx.view_handle)
+                    .unwrap()
+                    .get(sel_idx)
+                    .unwrap();
+                // The typed character was just inserted before the cursor.
+                let at = sel.cursor;
+                let Some(line) = ctx.buffer.line(at.row) else {
+                    continue;
+                };
+                let char_at =
+                    |column: Column| (column >= 0).then(|| line.chars().nth(column as usize))?;
+                if char_at(at.column - 1) != Some(typed) {
+                    continue;
+                }
+                let before = char_at(at.column - 2);
+                let next = char_at(at.column);
+
+                // Typing the closing character of an auto-inserted pair steps over it.
+                let closer_idx = ctx
+                    .buffer
+                    .auto_pair_closers
+                    .iter()
+                    .position(|closer| closer.cursor == at);
+                if let Some(closer_idx) = closer_idx
+                    && next == Some(typed)
+                    && is_closer(typed)
+                {
+                    ctx.buffer.auto_pair_closers.remove(closer_idx);
+                    ctx.buffer.delete_at(at)?;
+                    modified = true;
+                    continue;
+                }
+
+                let Some((open, close)) = opening_pair else {
+                    continue;
+                };
+                if !should_auto_pair(open, close, before, next, is_closer) {
+                    continue;
+                }
+                ctx.buffer.insert_char_at(at, close)?;
+                ctx.buffer
+                    .auto_pair_closers
+                    .push(Selection::new().with_anchor(at).with_cursor(at));
+
+                // The closing character goes after the cursor rather than before it.
+                let selection = ctx
+                    .buffer
+                    .view_selections_mut(ctx.view_handle)
+                    .unwrap()
+                    .get_mut(sel_idx)
+                    .unwrap();
+                let anchor = if sel.anchor == at {
+                    at
+                } else {
+                    selection.anchor
+                };
+                *selection = selection.with_anchor(anchor).with_cursor(at);
+                modified = true;
+            }
+
+            if modified {
+                ctx.queue.emit("buffer-modified", "");
+                ctx.queue.emit("selections-modified", "");
+            }
+
+            Ok(())
+        }),
+    );
+
+    // Meant to run before deleting the character before the cursor, so that
+    // deleting the opening character of an empty auto-inserted pair deletes
+    // its closing character as well.
+    cr.register(
+        "auto-pairs-delete",
+        focused_buffer_command(|_opt, ctx| {
+            let pairs = ctx.state.config.get_pairs();
+
+            // From the end of the buffer, so that the cursors yet to be handled don't move.
+            let mut cursors = ctx
+                .selections
+                .iter()
+                .map(|sel| sel.cursor)
+                .collect::<Vec<_>>();
+            cursors.sort_by(|a, b| b.cmp(a));
+
+            let mut modified = false;
+            for at in cursors {
+                let Some(closer_idx) = ctx
+                    .buffer
+                    .auto_pair_closers
+                    .iter()
+                    .position(|closer| closer.cursor == at)
+                else {
+                    continue;
+                };
+                let Some(line) = ctx.buffer.line(at.row) else {
+                    continue;
+                };
+                let mut chars = line.chars().skip((at.column as usize).saturating_sub(1));
+                let (previous, next) = (chars.next(), chars.next());
+                let is_empty_pair = at.column > 0
+                    && pairs
+                        .iter()
+                        .any(|&(open, close)| previous == Some(open) && next == Some(close));
+                if is_empty_pair {
+                    ctx.buffer.auto_pair_closers.remove(closer_idx);
+                    ctx.buffer.delete_at(at)?;
+                    modified = true;
+                }
+            }
+
+            if modified {
+                ctx.queue.emit("buffer-modified", "");
+            }
+
+            Ok(())
+        }),
+    );
+
+    cr.register(
+        "auto-pairs-forget",
+        focused_buffer_command(|_opt, ctx| {
+            ctx.buffer.auto_pair_closers.clear();
+            Ok(())
+        }),
+    );
 
     cr.register("selections-merge-overlapping", |_opt, ctx| {
         if let Some(view_handle) = ctx.state.focused_view() {
@@ -864,4 +1258,657 @@ pub fn register_editor_commands(cr: &mut CommandRegistry) {
             Ok(())
         }),
     );
+
+    cr.register(
+        "selections-split",
+        focused_buffer_command(|opt, ctx| {
+            let re_separator = Regex::new(opt).or_strerr()?;
+
+            let mut new_selections = Vec::new();
+            for sel in ctx.selections.iter() {
+                let start_idx = ctx.buffer.map_position_to_byte_index(sel.start()).unwrap();
+                let Some(text) = ctx.buffer.selection_text(sel) else { continue };
+
+                let mut piece_start = 0;
+                let separators = re_separator
+                    .find_iter(&text)
+                    .map(|matsh| matsh.range())
+                    .chain(std::iter::once(text.len()..text.len()));
+                for separator in separators {
+                    if piece_start < separator.start {
+                        let start = ctx
+                            .buffer
+                            .map_byte_index_to_position(start_idx + piece_start, false)
+                            .unwrap();
+                        let end = ctx
+                            .buffer
+                            .map_byte_index_to_position(start_idx + separator.start, true)
+                            .unwrap();
+                        let new_sel = if sel.is_forward() {
+                            Selection::new().with_anchor(start).with_cursor(end)
+                        } else {
+                            Selection::new().with_cursor(start).with_anchor(end)
+                        };
+                        new_selections.push(new_sel);
+                    }
+                    piece_start = separator.end;
+                }
+            }
+
+            set_derived_selections(ctx.buffer, ctx.view_handle, new_selections)?;
+            ctx.queue.emit("selections-modified", "");
+
+            Ok(())
+        }),
+    );
+
+    cr.register(
+        "selections-split-lines",
+        focused_buffer_command(|_opt, ctx| {
+            let mut new_selections = Vec::new();
+            for sel in ctx.selections.iter() {
+                for line_sel in sel.split_lines() {
+                    let line_sel = ctx.buffer.limit_selection_to_content(&line_sel);
+                    let new_sel = if sel.is_forward() {
+                        line_sel
+                    } else {
+                        line_sel.flipped()
+                    };
+                    new_selections.push(new_sel);
+                }
+            }
+
+            set_derived_selections(ctx.buffer, ctx.view_handle, new_selections)?;
+            ctx.queue.emit("selections-modified", "");
+
+            Ok(())
+        }),
+    );
+
+    cr.register("selections-keep", selections_filter_command(true));
+    cr.register("selections-drop", selections_filter_command(false));
+
+    cr.register(
+        "case-convert",
+        focused_buffer_command(|opt, ctx| {
+            let case = Case::parse(opt.trim()).ok_or_else(|| {
+                format!(
+                    "unknown case '{opt}' (lower, upper, swap, title, snake, camel, pascal, kebab, screaming)"
+                )
+            })?;
+
+            let texts = ctx
+                .selections
+                .iter()
+                .map(|sel| {
+                    let text = ctx.buffer.selection_text(sel)?;
+                    let converted = convert_case(&text, case);
+                    (converted != text).then_some(converted)
+                })
+                .collect::<Vec<_>>();
+            ctx.buffer
+                .replace_view_selections_text(ctx.view_handle, &texts)?;
+
+            ctx.queue.emit("buffer-modified", "");
+            ctx.queue.emit("selections-modified", "");
+            ctx.queue.push("history-save");
+
+            Ok(())
+        }),
+    );
+
+    cr.register(
+        "selections-align",
+        focused_buffer_command(|opt, ctx| {
+            let opts = Options::new().flag("ends").parse(opt)?;
+            let align_ends = opts.contains("ends");
+            let aligned_position = |sel: &Selection| {
+                if align_ends { sel.end() } else { sel.start() }
+            };
+
+            // The n-th selection of each line is aligned with the n-th
+            // selection of the other lines.
+            let mut indices_per_row: BTreeMap<Row, Vec<usize>> = BTreeMap::new();
+            for (i, sel) in ctx.selections.iter().enumerate() {
+                indices_per_row
+                    .entry(aligned_position(sel).row)
+                    .or_default()
+                    .push(i);
+            }
+            for indices in indices_per_row.values_mut() {
+                indices.sort_by_key(|&i| aligned_position(&ctx.selections.get(i).unwrap()));
+            }
+            let column_count = indices_per_row.values().map(Vec::len).max().unwrap_or(0);
+
+            for n in 0..column_count {
+                // Padding shifts the selections that follow on the same line,
+                // so they are fetched again for each column.
+                let selections = ctx.buffer.view_selections(ctx.view_handle).unwrap().clone();
+                let aligned = indices_per_row
+                    .values()
+                    .filter_map(|indices| indices.get(n))
+                    .map(|&i| {
+                        let sel = selections.get(i).unwrap();
+                        let position = aligned_position(&sel);
+                        let logical_column = ctx
+                            .buffer
+                            .map_true_position_to_logical_position(position, &ctx.state.config)
+                            .column;
+                        (sel, position, logical_column)
+                    })
+                    .collect::<Vec<_>>();
+                let Some(target_column) = aligned.iter().map(|(_, _, column)| *column).max() else {
+                    continue;
+                };
+
+                for (sel, position, logical_column) in aligned {
+                    let padding = target_column - logical_column;
+                    if padding <= 0 {
+                        continue;
+                    }
+                    // When aligning the end of a multiline selection, the
+                    // padding goes on the line of its end.
+                    let insert_at = if sel.start().row == position.row {
+                        sel.start()
+                    } else {
+                        Position::new(0, position.row)
+                    };
+                    ctx.buffer
+                        .insert_str_at(insert_at, &" ".repeat(padding as usize))?;
+                }
+            }
+
+            ctx.queue.emit("buffer-modified", "");
+            ctx.queue.emit("selections-modified", "");
+
+            Ok(())
+        }),
+    );
+}
+
+/// Keeps the selections which contain a match of the regex, or which don't
+/// when `keep_matching` is false.
+fn selections_filter_command(
+    keep_matching: bool,
+) -> impl Fn(&str, ExecuteCommandContext) -> Result<(), String> {
+    focused_buffer_command(move |opt, ctx| {
+        let regex = Regex::new(opt).or_strerr()?;
+
+        let new_selections = ctx
+            .selections
+            .iter()
+            .filter(|sel| {
+                let text = ctx.buffer.selection_text(sel).unwrap_or_default();
+                regex.is_match(&text) == keep_matching
+            })
+            .copied()
+            .collect();
+
+        set_derived_selections(ctx.buffer, ctx.view_handle, new_selections)?;
+        ctx.queue.emit("selections-modified", "");
+
+        Ok(())
+    })
+}
+
+/// Replaces the view's selections with ones derived from them, in the same
+/// order. Since the primary selection comes first, it stays primary when it
+/// is kept, or it passes on to the next one derived from it.
+fn set_derived_selections(
+    buffer: &mut TextBuffer,
+    view_handle: Handle<View>,
+    mut new_selections: Vec<Selection>,
+) -> Result<(), String> {
+    if new_selections.is_empty() {
+        return Err("no selections left".to_string());
+    }
+    let sels = buffer.view_selections_mut(view_handle).unwrap();
+    sels.primary_selection = new_selections.remove(0);
+    sels.extra_selections = new_selections;
+    Ok(())
+}
+
+/// Pairs are only inserted before whitespace or a closing character, and pairs
+/// of quotes (with the same opening and closing character) not right after a
+/// word, like in `don't`.
+fn should_auto_pair(
+    open: char,
+    close: char,
+    before: Option<char>,
+    next: Option<char>,
+    is_closer: impl Fn(char) -> bool,
+) -> bool {
+    let is_word_char = |ch: char| ch.is_alphanumeric() || ch == '_';
+    let next_allows_pair = next.is_none_or(|ch| is_whitespace(ch) || is_closer(ch));
+    if open == close {
+        next_allows_pair && !before.is_some_and(is_word_char)
+    } else {
+        next_allows_pair
+    }
+}
+
+enum GotoTarget {
+    /// 1-based line, and column if any.
+    Line(Row, Option<Column>),
+    /// Lines down (or up, when negative) from the cursor.
+    Relative(Row),
+    /// Percentage of the buffer's lines.
+    Percentage(Row),
+}
+
+fn parse_goto_options(opt: &str) -> Result<(GotoTarget, bool), String> {
+    let opts = Options::new().flag("anchored").parse(opt)?;
+    Ok((
+        GotoTarget::parse(opts.remainder())?,
+        opts.contains("anchored"),
+    ))
+}
+
+impl GotoTarget {
+    fn parse(src: &str) -> Result<Self, String> {
+        let parse_number = |s: &str| {
+            s.parse::<Row>()
+                .map_err(|_| format!("invalid goto target '{src}'"))
+        };
+
+        if let Some(percentage) = src.strip_suffix('%') {
+            Ok(Self::Percentage(parse_number(percentage)?.clamp(0, 100)))
+        } else if src.starts_with(['+', '-']) {
+            Ok(Self::Relative(parse_number(src)?))
+        } else if let Some((line, column)) = src.split_once(':') {
+            Ok(Self::Line(parse_number(line)?, Some(parse_number(column)?)))
+        } else if src.is_empty() {
+            Err("missing goto target".to_string())
+        } else {
+            Ok(Self::Line(parse_number(src)?, None))
+        }
+    }
+
+    /// The target position, which may be past the content. Saturates, so that
+    /// huge numbers stay huge instead of wrapping around.
+    fn position(&self, buffer: &TextBuffer, cursor: Position) -> Position {
+        match *self {
+            Self::Line(line, column) => Position::new(
+                column.map_or(0, |column| column.saturating_sub(1)),
+                line.saturating_sub(1),
+            ),
+            Self::Relative(offset) => cursor.with_row(cursor.row.saturating_add(offset)),
+            Self::Percentage(percentage) => {
+                let row = i64::from(buffer.last_row()) * i64::from(percentage) / 100;
+                Position::new(0, row as Row)
+            }
+        }
+    }
+}
+
+/// Whether `line` was dedented by the indent rules before the character before
+/// `column` was typed.
+fn decreased_before_typing(line: &str, column: Column, rules: &IndentRules) -> bool {
+    let Some(typed) = (column as usize).checked_sub(1) else {
+        return false;
+    };
+    let before_typing = line
+        .chars()
+        .enumerate()
+        .filter_map(|(i, ch)| (i != typed).then_some(ch))
+        .collect::<String>();
+    rules.decreases(&before_typing)
+}
+
+/// The indentation width of `row` according to the indent rules: that of the
+/// previous line that isn't blank nor kept, one level more if that line
+/// increases indentation, and one level less if `dedent` and `row` decreases it.
+fn indent_from_rules(
+    buffer: &TextBuffer,
+    row: Row,
+    rules: &IndentRules,
+    editor: &EditorConfig,
+    dedent: bool,
+) -> i32 {
+    let indent_size = editor.indent_size.max(1);
+    let reference = (0..row)
+        .rev()
+        .filter_map(|row| buffer.line(row))
+        .find(|line| !line.trim().is_empty() && !rules.keeps(line));
+
+    let mut level_mod = 0;
+    let reference_indent_width = match reference {
+        Some(reference) => {
+            if rules.increases(reference) {
+                level_mod += 1;
+            }
+            editor.indentation_width(take_while(reference, is_whitespace).0)
+        }
+        None => 0,
+    };
+    if dedent && buffer.line(row).is_some_and(|line| rules.decreases(line)) {
+        level_mod -= 1;
+    }
+
+    i32::max(reference_indent_width / indent_size + level_mod, 0) * indent_size
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{commands::test_utils::TestEditor, config::make_builtin_config};
+
+    /// The content and selections after running `command`.
+    fn run(content: &str, selections: &str, command: &str) -> (String, String) {
+        let mut editor = TestEditor::new("a.txt", content, selections);
+        editor.run(command).unwrap();
+        (editor.text(), editor.selections())
+    }
+
+    /// The indentation width of the last line of `content` according to the
+    /// builtin indent rules of `file`.
+    fn rules_indent(file: &str, content: &str, dedent: bool) -> i32 {
+        let mut config = make_builtin_config().unwrap();
+        config.set_state(ConfigState::FILE, file);
+        let mut buffer = TextBuffer::new_empty();
+        buffer.lines = content.split('\n').map(str::to_string).collect();
+        let row = buffer.last_row();
+        indent_from_rules(
+            &buffer,
+            row,
+            config.get_indent_rules(),
+            config.get_editor(),
+            dedent,
+        )
+    }
+
+    #[test]
+    fn python_indent_rules() {
+        assert_eq!(rules_indent("a.py", "if x:\n", true), 4);
+        assert_eq!(rules_indent("a.py", "if x:  # why\n", true), 4);
+        assert_eq!(rules_indent("a.py", "if x:\n    y\nelse:", true), 0);
+        assert_eq!(rules_indent("a.py", "if x:\n    y\nelse:", false), 4);
+        assert_eq!(rules_indent("a.py", "if x:\n    y\nelsewhere", true), 4);
+        assert_eq!(rules_indent("a.py", "if x:\n    # comment:\n", true), 4);
+    }
+
+    #[test]
+    fn ruby_indent_rules() {
+        assert_eq!(rules_indent("a.rb", "def f\n", true), 4);
+        assert_eq!(rules_indent("a.rb", "xs.each do |x|\n", true), 4);
+        assert_eq!(rules_indent("a.rb", "def f\n    x\nend", true), 0);
+        assert_eq!(rules_indent("a.rb", "def f\n    x\nending", true), 4);
+        assert_eq!(rules_indent("a.rb", "if x\n    y\nelse\n", true), 4);
+    }
+
+    #[test]
+    fn lua_indent_rules() {
+        assert_eq!(rules_indent("a.lua", "if x then\n", true), 4);
+        assert_eq!(rules_indent("a.lua", "local f = function(a, b)\n", true), 4);
+        assert_eq!(
+            rules_indent("a.lua", "for i = 1, 3 do\n    x()\nend", true),
+            0
+        );
+        assert_eq!(
+            rules_indent("a.lua", "repeat\n    x()\nuntil done", true),
+            0
+        );
+        assert_eq!(
+            rules_indent("a.lua", "if x then\n    y()\nendless()", true),
+            4
+        );
+    }
+
+    #[test]
+    fn selections_split() {
+        let (_, sels) = run("a, b,c", "0:0-0:5", r"selections-split ,\s*");
+        assert_eq!(sels, "0:0-0:0 0:3-0:3 0:5-0:5");
+
+        let (_, sels) = run(",é,,ü,", "0:0-0:5", "selections-split ,");
+        assert_eq!(sels, "0:1-0:1 0:4-0:4");
+
+        // The pieces of the primary selection come first.
+        let (_, sels) = run("a b\nc d", "1:0-1:2 0:0-0:2", "selections-split  ");
+        assert_eq!(sels, "1:0-1:0 1:2-1:2 0:0-0:0 0:2-0:2");
+
+        let mut editor = TestEditor::new("a.txt", "a", "0:0-0:0");
+        assert!(editor.run("selections-split a").is_err());
+        assert!(editor.run("selections-split (").is_err());
+        assert_eq!(editor.selections(), "0:0-0:0");
+    }
+
+    #[test]
+    fn selections_keep_and_drop() {
+        let words = "0:0-0:2 0:4-0:6 0:8-0:10";
+        let (_, sels) = run("foo bar baz", words, "selections-keep ^ba");
+        assert_eq!(sels, "0:4-0:6 0:8-0:10");
+
+        let (_, sels) = run("foo bar baz", words, "selections-drop r$");
+        assert_eq!(sels, "0:0-0:2 0:8-0:10");
+
+        let mut editor = TestEditor::new("a.txt", "foo bar baz", words);
+        assert!(editor.run("selections-keep x").is_err());
+        assert!(editor.run("selections-drop a|o").is_err());
+        assert_eq!(editor.selections(), words);
+    }
+
+    #[test]
+    fn selections_align() {
+        let (text, sels) = run("a=1\nbbb=2", "0:1-0:1 1:3-1:3", "selections-align");
+        assert_eq!(text, "a  =1\nbbb=2");
+        assert_eq!(sels, "0:3-0:3 1:3-1:3");
+
+        // The n-th selections of each line are aligned together.
+        let (text, _) = run(
+            "a=1, b=2\nccc=3, dd=4",
+            "0:1-0:1 0:6-0:6 1:3-1:3 1:9-1:9",
+            "selections-align",
+        );
+        assert_eq!(text, "a  =1, b =2\nccc=3, dd=4");
+
+        // Tabs are as wide as the indent size.
+        let (text, _) = run("\ta=1\n    bb=2", "0:2-0:2 1:6-1:6", "selections-align");
+        assert_eq!(text, "\ta =1\n    bb=2");
+    }
+
+    #[test]
+    fn selections_align_ends() {
+        let (text, sels) = run("a=1\nbbb=2", "0:0-0:0 1:0-1:2", "selections-align --ends");
+        assert_eq!(text, "  a=1\nbbb=2");
+        assert_eq!(sels, "0:2-0:2 1:0-1:2");
+
+        // The end of a multiline selection is padded on its own line.
+        let (text, _) = run(
+            "x\n(a\nb)=1\nccc=2",
+            "1:0-2:1 3:0-3:2",
+            "selections-align --ends",
+        );
+        assert_eq!(text, "x\n(a\n b)=1\nccc=2");
+    }
+
+    #[test]
+    fn goto_line_and_column() {
+        let content = "a\nbbbb\ncc\nd";
+        assert_eq!(run(content, "0:0-0:0", "goto 3").1, "2:0-2:0");
+        assert_eq!(run(content, "0:0-0:0", "goto 2:3").1, "1:2-1:2");
+        // Past the content, the last line or column is used.
+        assert_eq!(run(content, "0:0-0:0", "goto 3:10").1, "2:2-2:2");
+        assert_eq!(run(content, "0:0-0:0", "goto 10").1, "3:0-3:0");
+        assert_eq!(run(content, "0:0-0:0", "goto 0").1, "0:0-0:0");
+    }
+
+    #[test]
+    fn goto_relative_and_percentage() {
+        let content = (0..11)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(run(&content, "4:0-4:0", "goto +3").1, "7:0-7:0");
+        assert_eq!(run(&content, "4:0-4:0", "goto -3").1, "1:0-1:0");
+        assert_eq!(run(&content, "4:0-4:0", "goto -30").1, "0:0-0:0");
+        assert_eq!(run(&content, "0:0-0:0", "goto 50%").1, "5:0-5:0");
+        assert_eq!(run(&content, "0:0-0:0", "goto 100%").1, "10:0-10:0");
+        assert_eq!(run(&content, "0:0-0:0", "goto 300%").1, "10:0-10:0");
+
+        // Only relative moves keep the extra selections.
+        assert_eq!(
+            run(&content, "1:0-1:0 4:0-4:0", "goto +1").1,
+            "2:0-2:0 5:0-5:0"
+        );
+        assert_eq!(run(&content, "1:0-1:0 4:0-4:0", "goto 8").1, "7:0-7:0");
+    }
+
+    #[test]
+    fn goto_huge_numbers_saturate() {
+        let content = "a\nb\nc";
+        let max = Row::MAX;
+        assert_eq!(run(content, "1:0-1:0", &format!("goto {max}")).1, "2:0-2:0");
+        assert_eq!(
+            run(content, "1:0-1:0", &format!("goto +{max}")).1,
+            "2:0-2:0"
+        );
+        assert_eq!(
+            run(content, "1:0-1:0", &format!("goto -{max}")).1,
+            "0:0-0:0"
+        );
+        assert_eq!(
+            run(content, "1:0-1:0", &format!("goto 2:{max}")).1,
+            "1:1-1:1"
+        );
+    }
+
+    #[test]
+    fn goto_anchored() {
+        let (_, sels) = run("a\nb\nc", "0:0-0:0", "goto --anchored 3");
+        assert_eq!(sels, "0:0-2:0");
+    }
+
+    #[test]
+    fn goto_invalid_target() {
+        let mut editor = TestEditor::new("a.txt", "a\nb", "1:0-1:0");
+        for target in ["", "x", "1:x", "+", "x%", "99999999999999"] {
+            assert!(editor.run(&format!("goto {target}")).is_err(), "{target}");
+        }
+        assert_eq!(editor.selections(), "1:0-1:0");
+    }
+
+    #[test]
+    fn goto_is_a_jump() {
+        let mut editor = TestEditor::new("a.txt", "a\nb\nc", "1:0-1:0");
+        editor.run("goto 3").unwrap();
+        editor.run("goto 1").unwrap();
+        editor.run("jump-back").unwrap();
+        assert_eq!(editor.selections(), "2:0-2:0");
+        editor.run("jump-back").unwrap();
+        assert_eq!(editor.selections(), "1:0-1:0");
+
+        // Failing to parse the target records nothing.
+        let mut editor = TestEditor::new("a.txt", "a\nb\nc", "1:0-1:0");
+        editor.run("goto x").unwrap_err();
+        assert!(editor.run("jump-back").is_err());
+    }
+
+    /// An editor in insert mode, to type in like the user does.
+    fn insert_mode(content: &str, selections: &str) -> TestEditor {
+        let mut editor = TestEditor::new("a.txt", content, selections);
+        editor.run("set mode insert").unwrap();
+        editor
+    }
+
+    #[test]
+    fn typing_dedents_lines() {
+        let mut editor = insert_mode("fn f() {\n    x\n    ", "2:4-2:4");
+        editor.type_text("}").unwrap();
+        assert_eq!(editor.text(), "fn f() {\n    x\n}");
+
+        let mut editor = TestEditor::new("a.py", "if x:\n    y\n    ", "2:4-2:4");
+        editor.run("set mode insert").unwrap();
+        editor.type_text("else").unwrap();
+        assert_eq!(editor.text(), "if x:\n    y\nelse");
+        editor.type_text("w").unwrap();
+        assert_eq!(editor.text(), "if x:\n    y\n    elsew");
+    }
+
+    #[test]
+    fn typing_keeps_indentation() {
+        // Lines that the indent rules don't dedent.
+        let mut editor = insert_mode("{\n  x", "1:3-1:3");
+        editor.type_text(" = 1;").unwrap();
+        assert_eq!(editor.text(), "{\n  x = 1;");
+
+        // Lines that the indent rules dedent, but were indented otherwise.
+        let mut editor = insert_mode("{\n    x\n  }", "2:3-2:3");
+        editor.type_text(" // end").unwrap();
+        assert_eq!(editor.text(), "{\n    x\n  } // end");
+    }
+
+    #[test]
+    fn auto_pairs_insert_closer() {
+        let mut editor = insert_mode("f", "0:1-0:1");
+        editor.type_text("(").unwrap();
+        assert_eq!(editor.text(), "f()");
+        assert_eq!(editor.selections(), "0:2-0:2");
+
+        editor.type_text("[\"").unwrap();
+        assert_eq!(editor.text(), "f([\"\"])");
+        assert_eq!(editor.selections(), "0:4-0:4");
+
+        // Each cursor gets its own pair.
+        let mut editor = insert_mode("a \nb ", "0:2-0:2 1:2-1:2");
+        editor.type_text("{").unwrap();
+        assert_eq!(editor.text(), "a {}\nb {}");
+        assert_eq!(editor.selections(), "0:3-0:3 1:3-1:3");
+    }
+
+    #[test]
+    fn auto_pairs_left_alone() {
+        // Not before a word.
+        let mut editor = insert_mode("x", "0:0-0:0");
+        editor.type_text("(").unwrap();
+        assert_eq!(editor.text(), "(x");
+
+        // Quotes are not paired right after a word.
+        let mut editor = insert_mode("don", "0:3-0:3");
+        editor.type_text("'t").unwrap();
+        assert_eq!(editor.text(), "don't");
+
+        // Closing characters that weren't auto-inserted are not typed over.
+        let mut editor = insert_mode("f()", "0:2-0:2");
+        editor.type_text(")").unwrap();
+        assert_eq!(editor.text(), "f())");
+    }
+
+    #[test]
+    fn auto_pairs_type_over_closer() {
+        let mut editor = insert_mode("", "0:0-0:0");
+        editor.type_text("(a)").unwrap();
+        assert_eq!(editor.text(), "(a)");
+        assert_eq!(editor.selections(), "0:3-0:3");
+
+        editor.type_text(" [\"b\"]").unwrap();
+        assert_eq!(editor.text(), "(a) [\"b\"]");
+        assert_eq!(editor.selections(), "0:9-0:9");
+
+        // Once back in normal mode, the closers are typed like any character.
+        let mut editor = insert_mode("", "0:0-0:0");
+        editor.type_text("(").unwrap();
+        editor.run("set mode normal").unwrap();
+        editor.run("set mode insert").unwrap();
+        editor.type_text(")").unwrap();
+        assert_eq!(editor.text(), "())");
+    }
+
+    #[test]
+    fn auto_pairs_delete_pair() {
+        let mut editor = insert_mode("f", "0:1-0:1");
+        editor.type_text("((").unwrap();
+        editor.run("map-input <backspace>").unwrap();
+        assert_eq!(editor.text(), "f()");
+        assert_eq!(editor.selections(), "0:2-0:2");
+        editor.run("map-input <backspace>").unwrap();
+        assert_eq!(editor.text(), "f");
+        assert_eq!(editor.selections(), "0:1-0:1");
+
+        // Pairs that are no longer empty lose only their opening character.
+        let mut editor = insert_mode("", "0:0-0:0");
+        editor.type_text("(a").unwrap();
+        editor.run("map-input <left>").unwrap();
+        editor.run("map-input <backspace>").unwrap();
+        assert_eq!(editor.text(), "a)");
+    }
 }
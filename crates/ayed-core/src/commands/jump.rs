@@ -0,0 +1,208 @@
+use crate::{
+    command::{CommandRegistry, ExecuteCommandContext},
+    config::ConfigState,
+    selection::Selections,
+    slotmap::Handle,
+    state::{Jump, View},
+    utils::path_ext::PathExt,
+};
+
+pub fn register_jump_commands(cr: &mut CommandRegistry) {
+    cr.register("jump-record", |_opt, mut ctx| {
+        record_active_editor_jump(&mut ctx);
+        Ok(())
+    });
+
+    // Marks a command as a jump: the location before it runs is recorded.
+    cr.register("jump", |opt, ctx| {
+        if opt.is_empty() {
+            return Err("missing command".to_string());
+        }
+        ctx.queue.push("jump-record");
+        ctx.queue.push(opt);
+        Ok(())
+    });
+
+    cr.register("jump-track", |_opt, ctx| {
+        let Some(current) = ctx.state.active_editor_jump(ctx.resources) else {
+            return Ok(());
+        };
+        let threshold = ctx.state.config.get_editor().jump_threshold;
+
+        let jump_list = &mut ctx.state.jump_list;
+        if let Some(last_seen) = jump_list
+            .last_seen_selections
+            .replace(current.selections.clone())
+        {
+            let moved_rows = last_seen
+                .primary()
+                .cursor
+                .row
+                .abs_diff(current.selections.primary().cursor.row);
+            if moved_rows > threshold {
+                let jump = Jump {
+                    selections: last_seen,
+                    ..current
+                };
+                jump_list.record(jump, ctx.resources);
+            }
+        }
+
+        Ok(())
+    });
+
+    cr.register("jump-back", |_opt, ctx| {
+        let Some(current) = ctx.state.active_editor_jump(ctx.resources) else {
+            return Err("no active editor".to_string());
+        };
+        let jump = ctx
+            .state
+            .jump_list
+            .back(current, ctx.resources)
+            .ok_or("no previous jump")?;
+        restore_jump(jump, ctx)
+    });
+
+    cr.register("jump-forward", |_opt, ctx| {
+        let jump = ctx
+            .state
+            .jump_list
+            .forward(ctx.resources)
+            .ok_or("no next jump")?;
+        restore_jump(jump, ctx)
+    });
+
+    // Used when a jump reopens its closed buffer, whose content may have
+    // changed on disk since.
+    cr.register("__jump-restore-selections", |opt, ctx| {
+        let Some(view_handle) = ctx.state.active_editor_view else {
+            return Ok(());
+        };
+        let selections = Selections::parse(opt)?;
+        set_view_selections(ctx, view_handle, selections);
+        Ok(())
+    });
+}
+
+pub(super) fn record_active_editor_jump(ctx: &mut ExecuteCommandContext) {
+    if let Some(jump) = ctx.state.active_editor_jump(ctx.resources) {
+        ctx.state.jump_list.record(jump, ctx.resources);
+    }
+}
+
+pub(super) fn restore_jump(jump: Jump, ctx: ExecuteCommandContext) -> Result<(), String> {
+    if !ctx.resources.buffers.contains(jump.buffer) {
+        // The buffer was closed since, reopen it if it had a file.
+        let Some(path) = jump.path else {
+            return Err("the buffer of this jump was closed".to_string());
+        };
+        ctx.queue
+            .push(format!("edit --no-jump {}", path.to_str_or_err()?));
+        ctx.queue
+            .push(format!("__jump-restore-selections {}", jump.selections));
+        return Ok(());
+    }
+
+    let view_handle = ctx
+        .resources
+        .view_with_buffer(jump.buffer)
+        .ok_or("the buffer of this jump has no view")?;
+    if ctx.state.active_editor_view != Some(view_handle) {
+        ctx.state.set_active_editor_view(view_handle, ctx.resources);
+        let path = ctx.resources.buffers.get(jump.buffer).path();
+        let path = path.map(|p| p.to_str_or_err()).transpose()?.unwrap_or("");
+        ctx.queue.set_state(ConfigState::FILE, path);
+    }
+    set_view_selections(ctx, view_handle, jump.selections);
+
+    Ok(())
+}
+
+fn set_view_selections(
+    ctx: ExecuteCommandContext,
+    view_handle: Handle<View>,
+    selections: Selections,
+) {
+    let buffer_handle = ctx.resources.views.get(view_handle).buffer;
+    let buffer = ctx.resources.buffers.get_mut(buffer_handle);
+
+    // The buffer may have been edited since the jump was recorded.
+    let primary = buffer.limit_selection_to_content(&selections.primary_selection);
+    let extras = selections
+        .extra_selections
+        .iter()
+        .map(|sel| buffer.limit_selection_to_content(sel))
+        .collect::<Vec<_>>();
+    let selections = Selections::new_with(primary, &extras);
+
+    // Not a move to track: the jump list is already where it should be.
+    ctx.state.jump_list.last_seen_selections = Some(selections.clone());
+    if let Some(view_selections) = buffer.view_selections_mut(view_handle) {
+        *view_selections = selections;
+    }
+
+    ctx.queue.emit("selections-modified", "");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::test_utils::TestEditor;
+
+    fn numbered_lines(count: usize) -> String {
+        (0..count)
+            .map(|i| format!("line {i}"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn records_moves_past_the_threshold() {
+        let mut editor = TestEditor::new("a.txt", &numbered_lines(40), "0:0-0:0");
+        editor.run("selections-set 5:0-5:0").unwrap();
+        editor.run("selections-set 20:0-20:0").unwrap();
+        editor.run("selections-set 25:0-25:0").unwrap();
+
+        editor.run("jump-back").unwrap();
+        assert_eq!(editor.selections(), "5:0-5:0");
+        assert!(editor.run("jump-back").is_err());
+        editor.run("jump-forward").unwrap();
+        assert_eq!(editor.selections(), "25:0-25:0");
+        assert!(editor.run("jump-forward").is_err());
+    }
+
+    #[test]
+    fn goes_back_and_forth_between_buffers() {
+        let mut editor = TestEditor::new("a.txt", "a", "0:0-0:0");
+        let a = editor.buffer_handle();
+        editor.run("edit b.txt").unwrap();
+        let b = editor.buffer_handle();
+        assert_ne!(a, b);
+
+        editor.run("jump-back").unwrap();
+        assert_eq!(editor.buffer_handle(), a);
+        editor.run("jump-forward").unwrap();
+        assert_eq!(editor.buffer_handle(), b);
+
+        // Recording a jump forgets those that were ahead.
+        editor.run("jump-back").unwrap();
+        editor.run("jump goto 1").unwrap();
+        assert!(editor.run("jump-forward").is_err());
+    }
+
+    #[test]
+    fn jumps_follow_edits() {
+        let mut editor = TestEditor::new("a.txt", &numbered_lines(40), "15:5-15:6");
+        editor.run("goto 1").unwrap();
+
+        editor.run(r"insert-str ab\ncd\n").unwrap();
+        editor.run("jump-back").unwrap();
+        assert_eq!(editor.selections(), "17:5-17:6");
+        editor.run("jump-forward").unwrap();
+
+        editor.run("selections-set 0:0-1:2").unwrap();
+        editor.run("delete").unwrap();
+        editor.run("jump-back").unwrap();
+        assert_eq!(editor.selections(), "15:5-15:6");
+        assert_eq!(editor.buffer().line(15), Some("line 15"));
+    }
+}
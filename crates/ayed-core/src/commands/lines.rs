@@ -0,0 +1,269 @@
+use crate::{
+    command::{CommandRegistry, helpers::focused_buffer_command, options::Options},
+    position::{Column, Position, Row},
+    selection::{Selection, Selections},
+    state::TextBuffer,
+    utils::string_utils::ops::is_whitespace,
+};
+
+pub fn register_lines_commands(cr: &mut CommandRegistry) {
+    cr.register(
+        "lines-join",
+        focused_buffer_command(|opt, ctx| {
+            let opts = Options::new().flag("no-separator").parse(opt)?;
+            let separator = match opts.remainder() {
+                _ if opts.contains("no-separator") => "",
+                "" => " ",
+                separator => separator,
+            };
+
+            // From the end of the buffer, so that the rows yet to be joined don't move.
+            for &(first, last) in row_ranges(&ctx.selections, 0).iter().rev() {
+                // A selection within a single line joins it with the next one.
+                let join_count = (last - first).max(1);
+                for _ in 0..join_count {
+                    if first >= ctx.buffer.last_row() {
+                        break;
+                    }
+                    join_line_with_next(ctx.buffer, first, separator)?;
+                }
+            }
+
+            ctx.queue.emit("buffer-modified", "");
+            ctx.queue.emit("selections-modified", "");
+            ctx.queue.push("history-save");
+
+            Ok(())
+        }),
+    );
+
+    cr.register(
+        "lines-move",
+        focused_buffer_command(|opt, ctx| {
+            let opts = Options::new().flag("up").flag("down").parse(opt)?;
+            let up = match (opts.contains("up"), opts.contains("down")) {
+                (true, false) => true,
+                (false, true) => false,
+                _ => return Err("expected either --up or --down".to_string()),
+            };
+
+            // Adjacent ranges move as one block, so that each swaps with a
+            // line that isn't selected.
+            let ranges = row_ranges(&ctx.selections, 1);
+            let last_row = ctx.buffer.last_row();
+            if up && ranges.first().is_some_and(|&(first, _)| first == 0) {
+                return Err("can't move lines further up".to_string());
+            }
+            if !up && ranges.last().is_some_and(|&(_, last)| last == last_row) {
+                return Err("can't move lines further down".to_string());
+            }
+
+            if up {
+                for &(first, last) in &ranges {
+                    move_line(ctx.buffer, first - 1, last)?;
+                }
+            } else {
+                for &(first, last) in ranges.iter().rev() {
+                    move_line(ctx.buffer, last + 1, first)?;
+                }
+            }
+
+            ctx.queue.emit("buffer-modified", "");
+            ctx.queue.emit("selections-modified", "");
+            ctx.queue.push("history-save");
+
+            Ok(())
+        }),
+    );
+}
+
+/// The rows covered by the selections, in order. Ranges that overlap or are
+/// less than `max_gap` rows apart are merged.
+fn row_ranges(selections: &Selections, max_gap: Row) -> Vec<(Row, Row)> {
+    let mut ranges = selections
+        .iter()
+        .map(|sel| (sel.start().row, sel.end().row))
+        .collect::<Vec<_>>();
+    ranges.sort();
+
+    let mut merged: Vec<(Row, Row)> = Vec::new();
+    for (first, last) in ranges {
+        match merged.last_mut() {
+            Some((_, prev_last)) if first <= *prev_last + max_gap => {
+                *prev_last = (*prev_last).max(last);
+            }
+            _ => merged.push((first, last)),
+        }
+    }
+    merged
+}
+
+/// Joins `row` with the next row, replacing the indentation of the latter with
+/// `separator`. No separator is added next to an empty line.
+fn join_line_with_next(buffer: &mut TextBuffer, row: Row, separator: &str) -> Result<(), String> {
+    let next_line = buffer.line(row + 1).unwrap_or_default();
+    let indent_count = next_line
+        .chars()
+        .take_while(|&ch| is_whitespace(ch))
+        .count();
+    let next_is_blank = indent_count == next_line.chars().count();
+    if indent_count > 0 {
+        let indent = Selection::new()
+            .with_anchor(Position::new(0, row + 1))
+            .with_cursor(Position::new(indent_count as Column - 1, row + 1));
+        buffer.delete_selection(&indent)?;
+    }
+
+    let join_column = buffer.line_char_count(row).unwrap_or(0);
+    buffer.join_line_with_next(row)?;
+    if join_column > 0 && !next_is_blank && !separator.is_empty() {
+        buffer.insert_str_at(Position::new(join_column, row), separator)?;
+    }
+
+    Ok(())
+}
+
+/// Moves the line at `from` to `to`, with the selections on it. Lines in
+/// between shift by one row towards `from`.
+fn move_line(buffer: &mut TextBuffer, from: Row, to: Row) -> Result<(), String> {
+    let text = buffer.line(from).unwrap_or_default().to_string();
+    let char_count = text.chars().count() as Column;
+
+    // The line and its line break. The last line has none, so the line break
+    // before it goes instead.
+    let line_selection = if from < buffer.last_row() {
+        Selection::new()
+            .with_anchor(Position::new(0, from))
+            .with_cursor(Position::new(char_count, from))
+    } else {
+        let previous_end = Position::new(buffer.line_char_count(from - 1).unwrap_or(0), from - 1);
+        let end = if char_count == 0 {
+            previous_end
+        } else {
+            Position::new(char_count - 1, from)
+        };
+        Selection::new().with_anchor(previous_end).with_cursor(end)
+    };
+    buffer.delete_selection(&line_selection)?;
+
+    if to <= buffer.last_row() {
+        buffer.insert_str_at(Position::new(0, to), &format!("{text}\n"))?;
+    } else {
+        let at = buffer.end_position();
+        buffer.insert_str_at(at, &format!("\n{text}"))?;
+
+        // Selections at the end of the buffer stay on their line.
+        let end = buffer.end_position();
+        for selection in buffer
+            .selections
+            .values_mut()
+            .flat_map(Selections::iter_mut)
+        {
+            if selection.cursor == end {
+                *selection = selection.with_cursor(at);
+            }
+            if selection.anchor == end {
+                *selection = selection.with_anchor(at);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::test_utils::TestEditor;
+
+    /// The content and selections after running `command`.
+    fn run(content: &str, selections: &str, command: &str) -> (String, String) {
+        let mut editor = TestEditor::new("a.txt", content, selections);
+        editor.run(command).unwrap();
+        (editor.text(), editor.selections())
+    }
+
+    #[test]
+    fn join_lines() {
+        // A selection within a line joins it with the next one.
+        let (text, _) = run("a\n    b\nc", "0:0-0:0", "lines-join");
+        assert_eq!(text, "a b\nc");
+
+        let (text, _) = run("a\n  b\n\tc\nd", "0:0-2:0", "lines-join");
+        assert_eq!(text, "a b c\nd");
+
+        // No separator next to an empty or blank line.
+        let (text, _) = run("a\n   \nb", "0:0-1:0", "lines-join");
+        assert_eq!(text, "a\nb");
+        let (text, _) = run("\nb", "0:0-0:0", "lines-join");
+        assert_eq!(text, "b");
+
+        let (text, _) = run("a\n b\nc", "0:0-2:0", "lines-join ;");
+        assert_eq!(text, "a;b;c");
+        let (text, _) = run("a\n b", "0:0-0:0", "lines-join --no-separator");
+        assert_eq!(text, "ab");
+
+        // The last line has nothing to join with.
+        let (text, _) = run("a\nb", "1:0-1:0", "lines-join");
+        assert_eq!(text, "a\nb");
+    }
+
+    #[test]
+    fn join_lines_with_multiple_selections() {
+        let (text, sels) = run("a\nb\nc\nd\ne\nf", "0:0-0:0 2:0-4:0", "lines-join");
+        assert_eq!(text, "a b\nc d e\nf");
+        assert_eq!(sels, "0:0-0:0 1:0-1:4");
+    }
+
+    #[test]
+    fn move_lines() {
+        let (text, sels) = run("a\nb\nc", "1:0-1:0", "lines-move --up");
+        assert_eq!(text, "b\na\nc");
+        assert_eq!(sels, "0:0-0:0");
+
+        let (text, sels) = run("a\nb\nc", "0:0-1:0", "lines-move --down");
+        assert_eq!(text, "c\na\nb");
+        assert_eq!(sels, "1:0-2:0");
+
+        // To and from the last line, which has no line break.
+        let (text, sels) = run("a\nbb", "0:1-0:1", "lines-move --down");
+        assert_eq!(text, "bb\na");
+        assert_eq!(sels, "1:1-1:1");
+        let (text, sels) = run("a\nbb", "1:1-1:1", "lines-move --up");
+        assert_eq!(text, "bb\na");
+        assert_eq!(sels, "0:1-0:1");
+    }
+
+    #[test]
+    fn move_lines_with_multiple_selections() {
+        let (text, sels) = run("a\nb\nc\nd\ne", "1:0-1:0 3:0-3:0", "lines-move --up");
+        assert_eq!(text, "b\na\nd\nc\ne");
+        assert_eq!(sels, "0:0-0:0 2:0-2:0");
+
+        // Adjacent lines move as a block.
+        let (text, sels) = run("a\nb\nc\nd", "1:0-1:0 2:0-2:0", "lines-move --down");
+        assert_eq!(text, "a\nd\nb\nc");
+        assert_eq!(sels, "2:0-2:0 3:0-3:0");
+    }
+
+    #[test]
+    fn move_lines_past_the_edges() {
+        let mut editor = TestEditor::new("a.txt", "a\nb", "0:0-0:0 1:0-1:0");
+        assert!(editor.run("lines-move --up").is_err());
+        assert!(editor.run("lines-move --down").is_err());
+        assert!(editor.run("lines-move").is_err());
+        assert_eq!(editor.text(), "a\nb");
+    }
+
+    #[test]
+    fn undo_in_a_single_step() {
+        let mut editor = TestEditor::new("a.txt", "a\nb\nc\nd", "0:0-0:0 2:0-2:0");
+        editor.run("history-save").unwrap();
+        editor.run("lines-join").unwrap();
+        editor.run("history-undo").unwrap();
+        assert_eq!(editor.text(), "a\nb\nc\nd");
+
+        editor.run("lines-move --down").unwrap();
+        editor.run("history-undo").unwrap();
+        assert_eq!(editor.text(), "a\nb\nc\nd");
+    }
+}
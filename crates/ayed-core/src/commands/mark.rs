@@ -0,0 +1,197 @@
+use crate::{
+    command::{CommandRegistry, helpers::focused_buffer_command},
+    selection::{Selection, Selections},
+    slotmap::Handle,
+    state::{State, TextBuffer},
+};
+
+use super::jump::{record_active_editor_jump, restore_jump};
+
+pub fn register_mark_commands(cr: &mut CommandRegistry) {
+    cr.register("mark-save", |opt, ctx| {
+        let name = mark_name(opt)?;
+        let jump = ctx
+            .state
+            .active_editor_jump(ctx.resources)
+            .ok_or("no active editor")?;
+
+        // Mark names are shared by all buffers.
+        if let Some(previous) = ctx.state.marks.insert(name.to_string(), jump.clone())
+            && ctx.resources.buffers.contains(previous.buffer)
+        {
+            let buffer = ctx.resources.buffers.get_mut(previous.buffer);
+            buffer.marks.remove(name);
+        }
+        let buffer = ctx.resources.buffers.get_mut(jump.buffer);
+        buffer.marks.insert(name.to_string(), jump.selections);
+
+        ctx.state
+            .modeline
+            .set_message(format!("saved mark '{name}'"));
+
+        Ok(())
+    });
+
+    cr.register("mark-restore", |opt, mut ctx| {
+        let name = mark_name(opt)?;
+        let jump = ctx
+            .state
+            .mark(name, ctx.resources)
+            .ok_or_else(|| format!("no mark '{name}'"))?;
+
+        // Restoring a mark is a jump, which can be undone with `jump-back`.
+        record_active_editor_jump(&mut ctx);
+        restore_jump(jump, ctx)
+    });
+
+    cr.register("mark-delete", |opt, ctx| {
+        let name = mark_name(opt)?;
+        let mark = ctx
+            .state
+            .marks
+            .remove(name)
+            .ok_or_else(|| format!("no mark '{name}'"))?;
+        if ctx.resources.buffers.contains(mark.buffer) {
+            ctx.resources
+                .buffers
+                .get_mut(mark.buffer)
+                .marks
+                .remove(name);
+        }
+        Ok(())
+    });
+
+    cr.register(
+        "mark-union",
+        focused_buffer_command(|opt, ctx| {
+            let name = mark_name(opt)?;
+            let marked = mark_in_buffer(ctx.state, name, ctx.buffer_handle, ctx.buffer)?;
+
+            let mut extras = ctx.selections.extra_selections.clone();
+            extras.extend(marked.iter().copied());
+            *ctx.buffer.view_selections_mut(ctx.view_handle).unwrap() =
+                Selections::new_with(ctx.selections.primary_selection, &extras);
+
+            // Overlapping selections are merged by the `selections-modified` hooks.
+            ctx.queue.emit("selections-modified", "");
+
+            Ok(())
+        }),
+    );
+
+    cr.register(
+        "mark-intersect",
+        focused_buffer_command(|opt, ctx| {
+            let name = mark_name(opt)?;
+            let marked = mark_in_buffer(ctx.state, name, ctx.buffer_handle, ctx.buffer)?;
+
+            let mut intersections = Vec::new();
+            for sel in ctx.selections.iter() {
+                for other in marked.iter() {
+                    if let Some(intersection) = intersect(sel, other) {
+                        intersections.push(intersection);
+                    }
+                }
+            }
+            if intersections.is_empty() {
+                return Err("no selections left".to_string());
+            }
+
+            let primary = intersections.remove(0);
+            *ctx.buffer.view_selections_mut(ctx.view_handle).unwrap() =
+                Selections::new_with(primary, &intersections);
+
+            ctx.queue.emit("selections-modified", "");
+
+            Ok(())
+        }),
+    );
+}
+
+fn mark_name(opt: &str) -> Result<&str, String> {
+    match opt.split_whitespace().collect::<Vec<_>>()[..] {
+        [name] => Ok(name),
+        [] => Err("missing mark name".to_string()),
+        _ => Err(format!("invalid mark name '{opt}'")),
+    }
+}
+
+/// The selections of the mark `name`, if it was saved in `buffer`. Marks of
+/// a closed buffer belong to the buffer that reopened its file.
+fn mark_in_buffer(
+    state: &State,
+    name: &str,
+    buffer_handle: Handle<TextBuffer>,
+    buffer: &TextBuffer,
+) -> Result<Vec<Selection>, String> {
+    let mark = state.marks.get(name);
+    let selections = if mark.is_some_and(|mark| mark.buffer == buffer_handle) {
+        buffer.marks.get(name)
+    } else {
+        mark.filter(|mark| mark.path.is_some() && mark.path.as_deref() == buffer.path())
+            .map(|mark| &mark.selections)
+    };
+    selections
+        .map(|selections| {
+            selections
+                .iter()
+                .map(|sel| buffer.limit_selection_to_content(sel))
+                .collect()
+        })
+        .ok_or_else(|| format!("no mark '{name}' in this buffer"))
+}
+
+/// The part of `a` also covered by `b`, with the direction of `a`.
+fn intersect(a: &Selection, b: &Selection) -> Option<Selection> {
+    let start = a.start().max(b.start());
+    let end = a.end().min(b.end());
+    if start > end {
+        return None;
+    }
+    let intersection = Selection::new().with_anchor(start).with_cursor(end);
+    Some(if a.is_forward() {
+        intersection
+    } else {
+        intersection.flipped()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::test_utils::TestEditor;
+
+    #[test]
+    fn marks_follow_edits() {
+        let mut editor = TestEditor::new("a.txt", "a\nb\nhello world", "2:6-2:10 0:0-0:0");
+        editor.run("mark-save w").unwrap();
+
+        editor.run("selections-set 2:0-2:0").unwrap();
+        editor.run(r"insert-str xx\nyy ").unwrap();
+        editor.run("mark-restore w").unwrap();
+        assert_eq!(editor.selected_texts(), ["world", "a"]);
+
+        editor.run("selections-set 0:0-3:2").unwrap();
+        editor.run("delete").unwrap();
+        editor.run("mark-restore w").unwrap();
+        assert_eq!(editor.text(), "hello world");
+        // The marked `a` was deleted, leaving its selection on what followed.
+        assert_eq!(editor.selected_texts(), ["world", "h"]);
+    }
+
+    #[test]
+    fn combines_marks_with_selections() {
+        let mut editor = TestEditor::new("a.txt", "abc def", "0:0-0:2");
+        editor.run("mark-save m").unwrap();
+
+        editor.run("selections-set 0:4-0:6").unwrap();
+        editor.run("mark-union m").unwrap();
+        assert_eq!(editor.selected_texts(), ["def", "abc"]);
+
+        editor.run("selections-set 0:1-0:5").unwrap();
+        editor.run("mark-intersect m").unwrap();
+        assert_eq!(editor.selected_texts(), ["bc"]);
+
+        editor.run("mark-delete m").unwrap();
+        assert!(editor.run("mark-restore m").is_err());
+    }
+}
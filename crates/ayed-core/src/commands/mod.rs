@@ -1,15 +1,41 @@
 use crate::command::CommandRegistry;
 
+mod comment;
 mod config;
 mod core;
 mod editor;
+mod jump;
+mod lines;
 mod misc;
 mod lsp;
+mod mark;
+mod number;
+mod pipe;
+mod reflow;
+mod replace;
+mod search;
+mod sort;
+mod surround;
+#[cfg(test)]
+mod test_utils;
+mod whitespace;
 
 pub fn register_builtin_commands(cr: &mut CommandRegistry) {
     core::register_core_commands(cr);
     config::register_config_commands(cr);
+    comment::register_comment_commands(cr);
     editor::register_editor_commands(cr);
+    jump::register_jump_commands(cr);
+    lines::register_lines_commands(cr);
     misc::register_misc_commands(cr);
     lsp::register_lsp_commands(cr);
+    mark::register_mark_commands(cr);
+    number::register_number_commands(cr);
+    pipe::register_pipe_commands(cr);
+    reflow::register_reflow_commands(cr);
+    replace::register_replace_commands(cr);
+    search::register_search_commands(cr);
+    sort::register_sort_commands(cr);
+    surround::register_surround_commands(cr);
+    whitespace::register_whitespace_commands(cr);
 }
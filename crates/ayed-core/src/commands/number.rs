@@ -0,0 +1,294 @@
+use std::{ops::RangeInclusive, sync::LazyLock};
+
+use regex::Regex;
+
+use crate::{
+    command::{
+        CommandRegistry, ExecuteCommandContext, helpers::focused_buffer_command, options::Options,
+    },
+    position::{Column, Position},
+    selection::Selection,
+    state::TextBuffer,
+    utils::string_utils::{byte_index_to_char_index, char_index_to_byte_index},
+};
+
+static RE_NUMBER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?<sign>-)?(?:(?<radix>0[xX])(?<hex>[0-9a-fA-F]+)|(?<bradix>0[bB])(?<bin>[01]+)|(?<dec>[0-9]+))(?<suffix>_?(?:[ui](?:8|16|32|64|128|size)|f32|f64))?",
+    )
+    .unwrap()
+});
+
+pub fn register_number_commands(cr: &mut CommandRegistry) {
+    cr.register("number-increment", number_change_command(1));
+    cr.register("number-decrement", number_change_command(-1));
+}
+
+fn number_change_command(
+    direction: i128,
+) -> impl Fn(&str, ExecuteCommandContext) -> Result<(), String> {
+    focused_buffer_command(move |opt, ctx| {
+        let opts = Options::new().flag("sequence").parse(opt)?;
+        let sequence = opts.contains("sequence");
+        let amount = match opts.remainder() {
+            "" => 1,
+            n => n
+                .parse::<i128>()
+                .map_err(|_| format!("not a number: '{n}'"))?,
+        };
+
+        // In sequence mode, the n-th selection in the buffer changes by n×amount.
+        let mut order = (0..ctx.selections.count()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| ctx.selections.get(i).unwrap().start());
+
+        let mut texts = vec![None; ctx.selections.count()];
+        let mut claimed_numbers = Vec::new();
+        for (nth, &i) in order.iter().enumerate() {
+            let sel = ctx.selections.get(i).unwrap();
+            let Some(number) = find_number(ctx.buffer, sel.cursor) else {
+                continue;
+            };
+            let number_start = number.selection.start();
+            if claimed_numbers.contains(&number_start) {
+                continue;
+            }
+            claimed_numbers.push(number_start);
+
+            let factor = if sequence { nth as i128 + 1 } else { 1 };
+            let new_value = amount
+                .checked_mul(direction * factor)
+                .and_then(|change| number.value.checked_add(change))
+                .ok_or_else(|| "number out of range".to_string())?;
+            if let Some((suffix, range)) = &number.suffix_range
+                && !range.contains(&new_value)
+            {
+                return Err(format!("{new_value} is out of the range of {suffix}"));
+            }
+            texts[i] = Some(number.format(new_value));
+
+            let sels = ctx.buffer.view_selections_mut(ctx.view_handle).unwrap();
+            *sels.get_mut(i).unwrap() = number.selection;
+        }
+
+        if texts.iter().all(Option::is_none) {
+            return Err("no number found".to_string());
+        }
+
+        ctx.buffer
+            .replace_view_selections_text(ctx.view_handle, &texts)?;
+
+        ctx.queue.emit("buffer-modified", "");
+        ctx.queue.emit("selections-modified", "");
+        ctx.queue.push("history-save");
+
+        Ok(())
+    })
+}
+
+struct Number {
+    /// Covers the sign, radix prefix and digits, but not the type suffix.
+    selection: Selection,
+    value: i128,
+    radix: u32,
+    prefix: String,
+    digit_count: usize,
+    zero_padded: bool,
+    uppercase: bool,
+    /// The values allowed by the integer type suffix (ex: `u8`), if any.
+    suffix_range: Option<(String, RangeInclusive<i128>)>,
+}
+
+impl Number {
+    fn format(&self, value: i128) -> String {
+        let sign = if value < 0 { "-" } else { "" };
+        let magnitude = value.unsigned_abs();
+        let digits = match self.radix {
+            16 if self.uppercase => format!("{magnitude:X}"),
+            16 => format!("{magnitude:x}"),
+            2 => format!("{magnitude:b}"),
+            _ => format!("{magnitude}"),
+        };
+        let width = if self.zero_padded {
+            self.digit_count
+        } else {
+            0
+        };
+        format!("{sign}{}{digits:0>width$}", self.prefix)
+    }
+}
+
+/// Finds the number under `position`, or the first one after it on its line.
+fn find_number(buffer: &TextBuffer, position: Position) -> Option<Number> {
+    let line = buffer.line(position.row)?;
+    let cursor_idx = char_index_to_byte_index(line, position.column as usize)?;
+
+    for caps in RE_NUMBER.captures_iter(line) {
+        let matsh = caps.get(0).expect("group 0 cannot fail");
+        let suffix_len = caps.name("suffix").map_or(0, |m| m.len());
+        if matsh.end() <= cursor_idx {
+            continue;
+        }
+
+        // A minus right after an identifier or expression is a subtraction (ex: `x-1`).
+        let mut start = matsh.start();
+        let is_negative = caps.name("sign").is_some()
+            && !line[..start]
+                .chars()
+                .next_back()
+                .is_some_and(|ch| ch.is_alphanumeric() || ch == '_' || ch == ')');
+        if caps.name("sign").is_some() && !is_negative {
+            start += 1;
+        }
+
+        let (radix, prefix, digits) = if let Some(hex) = caps.name("hex") {
+            (16, &caps["radix"], hex.as_str())
+        } else if let Some(bin) = caps.name("bin") {
+            (2, &caps["bradix"], bin.as_str())
+        } else {
+            (10, "", &caps["dec"])
+        };
+        // Words containing digits, like `utf8` or `x86_64`, are not numbers.
+        if line[..start]
+            .chars()
+            .next_back()
+            .is_some_and(|ch| ch.is_alphabetic() || ch == '_')
+        {
+            continue;
+        }
+
+        let Ok(magnitude) = i128::from_str_radix(digits, radix) else {
+            continue;
+        };
+        let value = if is_negative { -magnitude } else { magnitude };
+
+        let end = matsh.end() - suffix_len;
+        let start_column = byte_index_to_char_index(line, start)? as Column;
+        let end_column = byte_index_to_char_index(line, end)? as Column - 1;
+        let selection = Selection::new()
+            .with_anchor(Position::new(start_column, position.row))
+            .with_cursor(Position::new(end_column, position.row));
+
+        return Some(Number {
+            selection,
+            value,
+            radix,
+            prefix: prefix.to_string(),
+            digit_count: digits.len(),
+            zero_padded: digits.len() > 1 && digits.starts_with('0'),
+            uppercase: digits.chars().any(|ch| ch.is_ascii_uppercase()),
+            suffix_range: caps.name("suffix").and_then(|suffix| {
+                let suffix = suffix.as_str().trim_start_matches('_');
+                Some((suffix.to_string(), suffix_range(suffix)?))
+            }),
+        });
+    }
+    None
+}
+
+/// The values representable by the integer type of a number suffix.
+fn suffix_range(suffix: &str) -> Option<RangeInclusive<i128>> {
+    let (min, max) = match suffix {
+        "u8" => (0, u8::MAX as i128),
+        "u16" => (0, u16::MAX as i128),
+        "u32" => (0, u32::MAX as i128),
+        "u64" => (0, u64::MAX as i128),
+        "usize" => (0, usize::MAX as i128),
+        // Greater values aren't found by `find_number` anyway.
+        "u128" => (0, i128::MAX),
+        "i8" => (i8::MIN as i128, i8::MAX as i128),
+        "i16" => (i16::MIN as i128, i16::MAX as i128),
+        "i32" => (i32::MIN as i128, i32::MAX as i128),
+        "i64" => (i64::MIN as i128, i64::MAX as i128),
+        "isize" => (isize::MIN as i128, isize::MAX as i128),
+        "i128" => (i128::MIN, i128::MAX),
+        _ => return None,
+    };
+    Some(min..=max)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::test_utils::TestEditor;
+
+    fn change(content: &str, selections: &str, command: &str) -> String {
+        let mut editor = TestEditor::new("a.txt", content, selections);
+        editor.run(command).unwrap();
+        editor.text()
+    }
+
+    #[test]
+    fn keeps_the_radix_and_case() {
+        assert_eq!(change("0xfe", "0:0-0:0", "number-increment"), "0xff");
+        assert_eq!(change("0XFF", "0:0-0:0", "number-increment"), "0X100");
+        assert_eq!(change("0xA0", "0:0-0:0", "number-decrement"), "0x9F");
+        assert_eq!(change("0b0111", "0:0-0:0", "number-increment"), "0b1000");
+        assert_eq!(change("0b1", "0:0-0:0", "number-increment 2"), "0b11");
+    }
+
+    #[test]
+    fn keeps_leading_zeros() {
+        assert_eq!(change("007", "0:0-0:0", "number-increment"), "008");
+        assert_eq!(change("099", "0:0-0:0", "number-increment"), "100");
+        assert_eq!(change("010", "0:0-0:0", "number-decrement"), "009");
+        assert_eq!(change("0", "0:0-0:0", "number-increment 10"), "10");
+    }
+
+    #[test]
+    fn changes_negative_numbers() {
+        assert_eq!(change("-1", "0:0-0:0", "number-increment"), "0");
+        assert_eq!(change("x = 0", "0:0-0:0", "number-decrement"), "x = -1");
+        assert_eq!(change("(-5)", "0:0-0:0", "number-decrement"), "(-6)");
+        // A minus after an identifier is a subtraction.
+        assert_eq!(change("x-1", "0:0-0:0", "number-increment"), "x-2");
+        assert_eq!(change("f(x)-1", "0:0-0:0", "number-decrement"), "f(x)-0");
+    }
+
+    #[test]
+    fn skips_digits_of_words() {
+        assert_eq!(change("utf8 12", "0:0-0:0", "number-increment"), "utf8 13");
+
+        let mut editor = TestEditor::new("a.txt", "utf8", "0:0-0:0");
+        assert!(editor.run("number-increment").is_err());
+    }
+
+    #[test]
+    fn respects_the_range_of_type_suffixes() {
+        assert_eq!(change("254u8", "0:0-0:0", "number-increment"), "255u8");
+        assert_eq!(change("-127i8", "0:0-0:0", "number-decrement"), "-128i8");
+        assert_eq!(change("1f32", "0:0-0:0", "number-increment"), "2f32");
+
+        for (content, command) in [
+            ("255u8", "number-increment"),
+            ("0usize", "number-decrement"),
+            ("-128i8", "number-decrement"),
+            ("0xffff_u16", "number-increment"),
+        ] {
+            let mut editor = TestEditor::new("a.txt", content, "0:0-0:0");
+            let result = editor.run(command);
+            assert!(result.is_err(), "{content}: {result:?}");
+            assert_eq!(editor.text(), content);
+        }
+    }
+
+    #[test]
+    fn changes_each_number_once() {
+        let text = change("1 1\n5", "0:0-0:0 0:2-0:2 1:0-1:0", "number-increment");
+        assert_eq!(text, "2 2\n6");
+
+        let text = change("19", "0:0-0:0 0:1-0:1", "number-increment");
+        assert_eq!(text, "20");
+    }
+
+    #[test]
+    fn changes_numbers_in_sequence() {
+        let text = change(
+            "0\n0\n0",
+            "2:0-2:0 0:0-0:0 1:0-1:0",
+            "number-increment --sequence",
+        );
+        assert_eq!(text, "1\n2\n3");
+
+        let text = change("0 0", "0:0-0:0 0:2-0:2", "number-decrement --sequence 5");
+        assert_eq!(text, "-5 -10");
+    }
+}
@@ -0,0 +1,256 @@
+use std::{
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::command::{
+    CommandRegistry,
+    helpers::{FocusedBufferCommandContext, focused_buffer_command},
+};
+
+pub fn register_pipe_commands(cr: &mut CommandRegistry) {
+    cr.register(
+        "pipe",
+        focused_buffer_command(|opt, ctx| {
+            let outputs = selection_outputs(opt, &ctx)?
+                .into_iter()
+                .map(Some)
+                .collect::<Vec<_>>();
+
+            ctx.buffer
+                .replace_view_selections_text(ctx.view_handle, &outputs)?;
+
+            ctx.queue.emit("buffer-modified", "");
+            ctx.queue.emit("selections-modified", "");
+            ctx.queue.push("history-save");
+
+            Ok(())
+        }),
+    );
+
+    cr.register(
+        "pipe-insert",
+        focused_buffer_command(|opt, mut ctx| {
+            let outputs = selection_outputs(opt, &ctx)?;
+
+            insert_outputs(&mut ctx, outputs, false)?;
+
+            ctx.queue.emit("buffer-modified", "");
+            ctx.queue.emit("selections-modified", "");
+            ctx.queue.push("history-save");
+
+            Ok(())
+        }),
+    );
+
+    cr.register(
+        "pipe-append",
+        focused_buffer_command(|opt, mut ctx| {
+            let outputs = selection_outputs(opt, &ctx)?;
+
+            insert_outputs(&mut ctx, outputs, true)?;
+
+            ctx.queue.emit("buffer-modified", "");
+            ctx.queue.emit("selections-modified", "");
+            ctx.queue.push("history-save");
+
+            Ok(())
+        }),
+    );
+
+    cr.register(
+        "pipe-keep",
+        focused_buffer_command(|opt, ctx| {
+            let mut kept = Vec::new();
+            for sel in ctx.selections.iter() {
+                let input = ctx.buffer.selection_text(sel).unwrap_or_default();
+                let output = run_shell_command(opt, &input, &ctx.state.working_directory)?;
+                if output.success {
+                    kept.push(*sel);
+                }
+            }
+
+            if kept.is_empty() {
+                return Err("no selections left".to_string());
+            }
+            let sels = ctx.buffer.view_selections_mut(ctx.view_handle).unwrap();
+            sels.primary_selection = kept.remove(0);
+            sels.extra_selections = kept;
+
+            ctx.queue.emit("selections-modified", "");
+
+            Ok(())
+        }),
+    );
+}
+
+struct ShellOutput {
+    success: bool,
+    status: String,
+    stdout: String,
+    stderr: String,
+}
+
+impl ShellOutput {
+    /// The stdout of the command, or an error made of its stderr if it failed.
+    fn checked(self) -> Result<String, String> {
+        if self.success {
+            return Ok(self.stdout);
+        }
+        let stderr = self.stderr.trim();
+        if stderr.is_empty() {
+            Err(format!("command failed ({})", self.status))
+        } else {
+            Err(format!("command failed ({}): {stderr}", self.status))
+        }
+    }
+}
+
+fn run_shell_command(
+    command: &str,
+    input: &str,
+    working_directory: &Path,
+) -> Result<ShellOutput, String> {
+    if command.trim().is_empty() {
+        return Err("missing shell command".to_string());
+    }
+
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(working_directory)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("failed to run `{command}`: {err}"))?;
+
+    // Written from another thread, so that a command filling its stdout before
+    // reading all of its stdin can't block us.
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = input.to_string();
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let output = child
+        .wait_with_output()
+        .map_err(|err| format!("failed to run `{command}`: {err}"))?;
+    // The command may exit without reading its input, which is fine.
+    let _ = writer.join();
+
+    Ok(ShellOutput {
+        success: output.status.success(),
+        status: output.status.to_string(),
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    })
+}
+
+/// Runs the shell command with the text of each selection as input, failing if
+/// any of the runs fails.
+fn selection_outputs(
+    command: &str,
+    ctx: &FocusedBufferCommandContext,
+) -> Result<Vec<String>, String> {
+    ctx.selections
+        .iter()
+        .map(|sel| {
+            let input = ctx.buffer.selection_text(sel).unwrap_or_default();
+            run_shell_command(command, &input, &ctx.state.working_directory)?.checked()
+        })
+        .collect()
+}
+
+/// Inserts each output before (or after) its selection, which then covers the
+/// inserted text.
+fn insert_outputs(
+    ctx: &mut FocusedBufferCommandContext,
+    outputs: Vec<String>,
+    after: bool,
+) -> Result<(), String> {
+    let mut order = (0..ctx.selections.count()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| std::cmp::Reverse(ctx.selections.get(i).unwrap().start()));
+
+    for i in order {
+        let output = &outputs[i];
+        if output.is_empty() {
+            continue;
+        }
+        let sel = ctx
+            .buffer
+            .view_selections(ctx.view_handle)
+            .unwrap()
+            .get(i)
+            .unwrap();
+        let insert_at = if !after {
+            sel.start()
+        } else if sel.end() >= ctx.buffer.end_position() {
+            ctx.buffer.end_position()
+        } else {
+            ctx.buffer
+                .move_position_horizontally(sel.end(), 1)
+                .unwrap_or(sel.end())
+        };
+        let inserted = ctx.buffer.insert_str_at(insert_at, output)?;
+        let inserted = if sel.is_forward() {
+            inserted
+        } else {
+            inserted.flipped()
+        };
+        *ctx.buffer
+            .view_selections_mut(ctx.view_handle)
+            .unwrap()
+            .get_mut(i)
+            .unwrap() = inserted;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::test_utils::TestEditor;
+
+    const WORDS: &str = "0:0-0:2 0:4-0:6";
+
+    #[test]
+    fn pipes_each_selection() {
+        let mut editor = TestEditor::new("a.txt", "abc def", WORDS);
+        editor.run("pipe tr a-z A-Z").unwrap();
+        assert_eq!(editor.text(), "ABC DEF");
+
+        // The selections then cover the inserted outputs.
+        let mut editor = TestEditor::new("a.txt", "abc def", WORDS);
+        editor.run("pipe-insert printf '<'").unwrap();
+        assert_eq!(editor.text(), "<abc <def");
+        assert_eq!(editor.selections(), "0:0-0:0 0:5-0:5");
+        editor.run("pipe-append printf '>'").unwrap();
+        assert_eq!(editor.text(), "<>abc <>def");
+    }
+
+    #[test]
+    fn keeps_selections_the_command_succeeds_on() {
+        let mut editor = TestEditor::new("a.txt", "1 2 10", "0:0-0:0 0:2-0:2 0:4-0:5");
+        editor.run("pipe-keep grep -q 1").unwrap();
+        assert_eq!(editor.selections(), "0:0-0:0 0:4-0:5");
+
+        assert!(editor.run("pipe-keep false").is_err());
+        assert_eq!(editor.selections(), "0:0-0:0 0:4-0:5");
+    }
+
+    #[test]
+    fn reports_failing_commands() {
+        let mut editor = TestEditor::new("a.txt", "abc def", WORDS);
+        let err = editor.run("pipe echo oops >&2; exit 3").unwrap_err();
+        assert!(err.contains("oops"), "{err}");
+        assert!(editor.run("pipe").is_err());
+        assert_eq!(editor.text(), "abc def");
+    }
+
+    #[test]
+    fn replaces_invalid_utf8_output() {
+        let mut editor = TestEditor::new("a.txt", "abc", "0:0-0:2");
+        editor.run(r"pipe printf 'a\377b'").unwrap();
+        assert_eq!(editor.text(), "a\u{fffd}b");
+    }
+}
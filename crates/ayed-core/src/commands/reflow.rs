@@ -0,0 +1,427 @@
+use crate::{
+    command::{CommandRegistry, helpers::focused_buffer_command},
+    panels::FocusedPanel,
+    position::{Column, Position, Row},
+    selection::Selection,
+    state::TextBuffer,
+    utils::string_utils::{char_count, ops::is_whitespace},
+};
+
+pub fn register_reflow_commands(cr: &mut CommandRegistry) {
+    cr.register(
+        "reflow",
+        focused_buffer_command(|opt, ctx| {
+            let width = match opt {
+                "" => ctx.buffer.editor_config(&ctx.state.config).text_width,
+                width => width
+                    .parse::<u32>()
+                    .map_err(|_| format!("invalid width '{width}'"))?,
+            } as usize;
+            let comment_token = ctx.state.config.get_comment().line.as_deref();
+
+            let mut ranges = ctx
+                .selections
+                .iter()
+                .map(|sel| {
+                    let (first, last) = (sel.start().row, sel.end().row);
+                    if first == last {
+                        paragraph_around(ctx.buffer, first, comment_token)
+                    } else {
+                        (first, last)
+                    }
+                })
+                .collect::<Vec<_>>();
+            ranges.sort();
+            ranges.dedup();
+
+            let mut modified = false;
+            // From the end of the buffer, so that the ranges yet to be reflowed don't move.
+            let mut next_first = Row::MAX;
+            for &(first, last) in ranges.iter().rev() {
+                // Overlapping ranges were reflowed with the range after them.
+                let last = last.min(next_first - 1);
+                if first > last {
+                    continue;
+                }
+                next_first = first;
+
+                let lines = (first..=last)
+                    .filter_map(|row| ctx.buffer.line(row))
+                    .collect::<Vec<_>>();
+                let reflowed = reflow_lines(&lines, width, comment_token);
+                if reflowed != lines {
+                    replace_rows(ctx.buffer, first, last, &reflowed.join("\n"))?;
+                    modified = true;
+                }
+            }
+
+            if modified {
+                ctx.queue.emit("buffer-modified", "");
+                ctx.queue.emit("selections-modified", "");
+                ctx.queue.push("history-save");
+            }
+
+            Ok(())
+        }),
+    );
+
+    cr.register(
+        "__auto-wrap-shim",
+        focused_buffer_command(|opt, ctx| {
+            let editor = ctx.buffer.editor_config(&ctx.state.config);
+            if !editor.auto_wrap || ctx.state.focused_panel != FocusedPanel::Editor {
+                return Ok(());
+            }
+            // Lines break when typing a word past the width, not a space.
+            if opt.chars().count() != 1 || opt.chars().all(is_whitespace) {
+                return Ok(());
+            }
+            let width = editor.text_width as usize;
+            let comment_token = ctx.state.config.get_comment().line.as_deref();
+
+            let mut modified = false;
+            let mut cursors = ctx
+                .selections
+                .iter()
+                .map(|sel| sel.cursor)
+                .collect::<Vec<_>>();
+            cursors.sort();
+            cursors.dedup_by_key(|cursor| cursor.row);
+            // From the end of the buffer, so that the rows yet to be wrapped don't move.
+            for cursor in cursors.into_iter().rev() {
+                let Some(line) = ctx.buffer.line(cursor.row) else {
+                    continue;
+                };
+                if cursor.column as usize <= width {
+                    continue;
+                }
+                let prefix = LinePrefix::parse(line, comment_token);
+                let Some((start, end)) = break_columns(line, &prefix, cursor.column, width) else {
+                    continue;
+                };
+
+                let blanks = Selection::new()
+                    .with_anchor(Position::new(start, cursor.row))
+                    .with_cursor(Position::new(end - 1, cursor.row));
+                let continuation = format!("\n{}", prefix.continuation);
+                ctx.buffer.delete_selection(&blanks)?;
+                ctx.buffer
+                    .insert_str_at(Position::new(start, cursor.row), &continuation)?;
+                modified = true;
+            }
+
+            if modified {
+                ctx.queue.emit("buffer-modified", "");
+                ctx.queue.emit("selections-modified", "");
+            }
+
+            Ok(())
+        }),
+    );
+}
+
+/// The prefix of a line that reflowing keeps: its indentation, comment token
+/// and Markdown list bullet.
+struct LinePrefix<'a> {
+    /// The prefix of the line itself.
+    first: &'a str,
+    /// The prefix of the lines that continue the paragraph of the line, where
+    /// the bullet is replaced by spaces so that the paragraph hangs.
+    continuation: String,
+    is_list_item: bool,
+    content: &'a str,
+}
+
+impl<'a> LinePrefix<'a> {
+    fn parse(line: &'a str, comment_token: Option<&str>) -> Self {
+        let blank_len = |s: &str| s.len() - s.trim_start_matches(is_whitespace).len();
+
+        let mut end = blank_len(line);
+        if let Some(token) = comment_token
+            && let Some(last) = token.chars().last()
+            && line[end..].starts_with(token)
+        {
+            end += token.len();
+            // Doc comments, like `///` and `//!`.
+            end += line[end..]
+                .chars()
+                .take_while(|&ch| ch == last || ch == '!')
+                .map(char::len_utf8)
+                .sum::<usize>();
+            end += blank_len(&line[end..]);
+        }
+
+        let bullet_len = bullet_len(&line[end..]);
+        let continuation = format!(
+            "{}{}",
+            &line[..end],
+            " ".repeat(char_count(&line[end..end + bullet_len]))
+        );
+        Self {
+            first: &line[..end + bullet_len],
+            continuation,
+            is_list_item: bullet_len > 0,
+            content: line[end + bullet_len..].trim_end(),
+        }
+    }
+
+    fn is_blank(&self) -> bool {
+        self.content.is_empty()
+    }
+}
+
+/// The length of the Markdown list bullet that `s` starts with, like `- ` or
+/// `1. `, with the blanks after it.
+fn bullet_len(s: &str) -> usize {
+    let marker_len = if s.starts_with(['-', '*', '+']) {
+        1
+    } else {
+        let digit_count = s.chars().take_while(char::is_ascii_digit).count();
+        if digit_count > 0 && s[digit_count..].starts_with(['.', ')']) {
+            digit_count + 1
+        } else {
+            return 0;
+        }
+    };
+    let rest = &s[marker_len..];
+    let blank_len = rest.len() - rest.trim_start_matches(is_whitespace).len();
+    if blank_len == 0 || blank_len == rest.len() {
+        return 0;
+    }
+    marker_len + blank_len
+}
+
+/// The rows of the paragraph around `row`, which is made of the lines that
+/// aren't blank and share the same prefix.
+fn paragraph_around(buffer: &TextBuffer, row: Row, comment_token: Option<&str>) -> (Row, Row) {
+    let prefix_at = |row: Row| Some(LinePrefix::parse(buffer.line(row)?, comment_token));
+    let Some(prefix) = prefix_at(row) else {
+        return (row, row);
+    };
+    if prefix.is_blank() {
+        return (row, row);
+    }
+
+    let mut first = row;
+    let mut first_prefix = prefix;
+    while !first_prefix.is_list_item
+        && let Some(above) = prefix_at(first - 1)
+        && !above.is_blank()
+        && above.continuation == first_prefix.first
+    {
+        first -= 1;
+        first_prefix = above;
+    }
+
+    let continuation = first_prefix.continuation;
+    let mut last = row;
+    while let Some(below) = prefix_at(last + 1)
+        && !below.is_blank()
+        && !below.is_list_item
+        && below.first == continuation
+    {
+        last += 1;
+    }
+
+    (first, last)
+}
+
+/// Rewraps the paragraphs of `lines` so that they fit in `width` characters
+/// where possible. Blank lines are left as is.
+fn reflow_lines(lines: &[&str], width: usize, comment_token: Option<&str>) -> Vec<String> {
+    let mut reflowed = Vec::new();
+    let mut paragraph: Option<(LinePrefix, Vec<&str>)> = None;
+
+    for &line in lines {
+        let prefix = LinePrefix::parse(line, comment_token);
+        let continues_paragraph = paragraph.as_ref().is_some_and(|(first_prefix, _)| {
+            !prefix.is_blank() && !prefix.is_list_item && prefix.first == first_prefix.continuation
+        });
+        if continues_paragraph {
+            let (_, words) = paragraph.as_mut().unwrap();
+            words.extend(prefix.content.split_whitespace());
+            continue;
+        }
+
+        if let Some((first_prefix, words)) = paragraph.take() {
+            fill_paragraph(&mut reflowed, &first_prefix, &words, width);
+        }
+        if prefix.is_blank() {
+            reflowed.push(line.to_string());
+        } else {
+            let words = prefix.content.split_whitespace().collect();
+            paragraph = Some((prefix, words));
+        }
+    }
+    if let Some((first_prefix, words)) = paragraph {
+        fill_paragraph(&mut reflowed, &first_prefix, &words, width);
+    }
+
+    reflowed
+}
+
+/// Fills lines with as many words as fit in `width`, words longer than that
+/// getting a line of their own.
+fn fill_paragraph(lines: &mut Vec<String>, prefix: &LinePrefix, words: &[&str], width: usize) {
+    let mut line = prefix.first.to_string();
+    let mut line_char_count = char_count(&line);
+    let mut line_has_words = false;
+    for word in words {
+        let word_char_count = char_count(word);
+        if line_has_words && line_char_count + 1 + word_char_count > width {
+            lines.push(line);
+            line = prefix.continuation.clone();
+            line_char_count = char_count(&line);
+            line_has_words = false;
+        }
+        if line_has_words {
+            line.push(' ');
+            line_char_count += 1;
+        }
+        line.push_str(word);
+        line_char_count += word_char_count;
+        line_has_words = true;
+    }
+    lines.push(line);
+}
+
+/// The columns of the blanks to replace by a line break so that the word at
+/// `cursor_column` starts a new line, preferably at most `width` columns long.
+fn break_columns(
+    line: &str,
+    prefix: &LinePrefix,
+    cursor_column: Column,
+    width: usize,
+) -> Option<(Column, Column)> {
+    let chars = line.chars().collect::<Vec<_>>();
+    let prefix_char_count = char_count(prefix.first);
+    let cursor_column = (cursor_column as usize).min(chars.len());
+
+    let mut breaks = Vec::new();
+    let mut column = prefix_char_count;
+    while column < cursor_column {
+        if is_whitespace(chars[column]) {
+            let start = column;
+            while column < chars.len() && is_whitespace(chars[column]) {
+                column += 1;
+            }
+            if column < cursor_column {
+                breaks.push((start, column));
+            }
+        } else {
+            column += 1;
+        }
+    }
+
+    let (start, end) = breaks
+        .iter()
+        .rev()
+        .find(|&&(start, _)| start <= width)
+        .or(breaks.first())
+        .copied()?;
+    Some((start as Column, end as Column))
+}
+
+/// Replaces the lines from `first` to `last` by `text`.
+fn replace_rows(buffer: &mut TextBuffer, first: Row, last: Row, text: &str) -> Result<(), String> {
+    let last_line_char_count = buffer.line_char_count(last).unwrap_or(0);
+    if last_line_char_count > 0 || first < last {
+        let end = if last_line_char_count > 0 {
+            Position::new(last_line_char_count - 1, last)
+        } else {
+            Position::new(buffer.line_char_count(last - 1).unwrap_or(0), last - 1)
+        };
+        let rows = Selection::new()
+            .with_anchor(Position::new(0, first))
+            .with_cursor(end);
+        buffer.delete_selection(&rows)?;
+    }
+    buffer.insert_str_at(Position::new(0, first), text)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer(content: &str) -> TextBuffer {
+        let mut buffer = TextBuffer::new_empty();
+        buffer.lines = content.split('\n').map(str::to_string).collect();
+        buffer
+    }
+
+    #[test]
+    fn bullets() {
+        assert_eq!(bullet_len("- item"), 2);
+        assert_eq!(bullet_len("*   item"), 4);
+        assert_eq!(bullet_len("12. item"), 4);
+        assert_eq!(bullet_len("3) item"), 3);
+        assert_eq!(bullet_len("-item"), 0);
+        assert_eq!(bullet_len("- "), 0);
+        assert_eq!(bullet_len("1.5 times"), 0);
+        assert_eq!(bullet_len("text"), 0);
+    }
+
+    #[test]
+    fn reflows_paragraphs() {
+        assert_eq!(
+            reflow_lines(&["aaa bbb", "ccc ddd eee", "", "fff"], 11, None),
+            ["aaa bbb ccc", "ddd eee", "", "fff"]
+        );
+        // Words longer than the width get a line of their own.
+        assert_eq!(
+            reflow_lines(&["a bbbbbbbbbbbb c"], 5, None),
+            ["a", "bbbbbbbbbbbb", "c"]
+        );
+    }
+
+    #[test]
+    fn reflows_doc_comments() {
+        assert_eq!(
+            reflow_lines(&["    /// aaa bbb ccc", "    /// ddd"], 16, Some("//")),
+            ["    /// aaa bbb", "    /// ccc ddd"]
+        );
+        assert_eq!(
+            reflow_lines(&["//! aaa", "//! bbb", "//", "// ccc"], 80, Some("//")),
+            ["//! aaa bbb", "//", "// ccc"]
+        );
+    }
+
+    #[test]
+    fn reflows_hanging_bullets() {
+        assert_eq!(
+            reflow_lines(&["- aaa bbb ccc", "- ddd"], 9, None),
+            ["- aaa bbb", "  ccc", "- ddd"]
+        );
+        assert_eq!(
+            reflow_lines(&["# 1. aaa", "#    bbb ccc"], 80, Some("#")),
+            ["# 1. aaa bbb ccc"]
+        );
+    }
+
+    #[test]
+    fn paragraphs() {
+        let buffer = buffer("aaa\nbbb\n\n- ccc\n  ddd\n- eee\n// fff\n// ggg");
+        assert_eq!(paragraph_around(&buffer, 1, None), (0, 1));
+        assert_eq!(paragraph_around(&buffer, 2, None), (2, 2));
+        assert_eq!(paragraph_around(&buffer, 4, None), (3, 4));
+        assert_eq!(paragraph_around(&buffer, 3, None), (3, 4));
+        assert_eq!(paragraph_around(&buffer, 5, None), (5, 5));
+        assert_eq!(paragraph_around(&buffer, 7, Some("//")), (6, 7));
+    }
+
+    #[test]
+    fn breaks() {
+        let prefix = LinePrefix::parse("aaa bbb  ccc", None);
+        assert_eq!(break_columns("aaa bbb  ccc", &prefix, 12, 10), Some((7, 9)));
+        assert_eq!(break_columns("aaa bbb  ccc", &prefix, 12, 5), Some((3, 4)));
+        // The indentation and the bullet are not places to break.
+        let line = "  - aaaaaaaaaa";
+        let prefix = LinePrefix::parse(line, None);
+        assert_eq!(break_columns(line, &prefix, 14, 5), None);
+        // Overlong words break at the first blank.
+        let line = "aaaaaaaaaa bbb ccc";
+        let prefix = LinePrefix::parse(line, None);
+        assert_eq!(break_columns(line, &prefix, 18, 5), Some((10, 11)));
+    }
+}
@@ -0,0 +1,330 @@
+use std::ops::Range;
+
+use regex::Regex;
+
+use crate::{
+    command::{
+        CommandRegistry, ExecuteCommandContext, helpers::focused_buffer_command, options::Options,
+    },
+    selection::{Selection, Selections},
+    state::{InteractiveReplace, TextBuffer},
+};
+
+pub fn register_replace_commands(cr: &mut CommandRegistry) {
+    cr.register(
+        "replace-regex",
+        focused_buffer_command(|opt, ctx| {
+            let opts = Options::new()
+                .flag("buffer")
+                .flag("interactive")
+                .parse(opt)?;
+            let Some((pattern, template)) = parse_substitution(opts.remainder()) else {
+                return Err(
+                    "usage: replace-regex [--buffer] [--interactive] /<pattern>/<replacement>/"
+                        .to_string(),
+                );
+            };
+            let regex = Regex::new(&pattern).map_err(|e| e.to_string())?;
+
+            let scopes = if opts.contains("buffer") {
+                let whole_buffer = 0..ctx.buffer.content_to_string().len();
+                vec![whole_buffer]
+            } else {
+                ctx.selections
+                    .iter()
+                    .filter_map(|sel| selection_byte_range(ctx.buffer, sel))
+                    .collect()
+            };
+            let replacements = collect_replacements(ctx.buffer, &regex, &template, &scopes);
+            if replacements.is_empty() {
+                return Err(format!("no match for '{pattern}'"));
+            }
+
+            if opts.contains("interactive") {
+                let previous_mode = ctx.state.config.state_value("mode").unwrap_or("normal");
+                ctx.state.interactive_replace = Some(InteractiveReplace {
+                    view: ctx.view_handle,
+                    pending: replacements.into(),
+                    replaced_count: 0,
+                    previous_mode: previous_mode.to_string(),
+                });
+                ctx.queue.set_state("mode", "replace-interactive");
+                ctx.queue.push("replace-regex-step");
+                return Ok(());
+            }
+
+            // Replace from the end so that the ranges of the replacements that
+            // are yet to be applied stay valid.
+            for (range, replacement) in replacements.iter().rev() {
+                apply_replacement(ctx.buffer, range.clone(), replacement)?;
+            }
+            ctx.state
+                .modeline
+                .set_message(replacement_count_message(replacements.len()));
+
+            ctx.queue.emit("buffer-modified", "");
+            ctx.queue.emit("selections-modified", "");
+            ctx.queue.push("history-save");
+
+            Ok(())
+        }),
+    );
+
+    cr.register("replace-regex-accept", |_opt, ctx| {
+        let Some(replace) = &mut ctx.state.interactive_replace else {
+            return Err("no interactive replace in progress".to_string());
+        };
+        let Some((range, replacement)) = replace.pending.pop_front() else {
+            return Ok(());
+        };
+
+        let buffer_handle = ctx.resources.views.get(replace.view).buffer;
+        let buffer = ctx.resources.buffers.get_mut(buffer_handle);
+        apply_replacement(buffer, range.clone(), &replacement)?;
+        replace.replaced_count += 1;
+
+        // Following matches are shifted by the difference in length.
+        let old_len = range.len();
+        for (pending_range, _) in replace.pending.iter_mut() {
+            let start = pending_range.start - old_len + replacement.len();
+            *pending_range = start..start + pending_range.len();
+        }
+
+        ctx.queue.emit("buffer-modified", "");
+        ctx.queue.push("replace-regex-step");
+
+        Ok(())
+    });
+
+    cr.register("replace-regex-skip", |_opt, ctx| {
+        if let Some(replace) = &mut ctx.state.interactive_replace {
+            replace.pending.pop_front();
+        }
+        ctx.queue.push("replace-regex-step");
+        Ok(())
+    });
+
+    cr.register("replace-regex-all", |_opt, ctx| {
+        let Some(replace) = &mut ctx.state.interactive_replace else {
+            return Err("no interactive replace in progress".to_string());
+        };
+
+        let buffer_handle = ctx.resources.views.get(replace.view).buffer;
+        let buffer = ctx.resources.buffers.get_mut(buffer_handle);
+        for (range, replacement) in replace.pending.drain(..).rev() {
+            apply_replacement(buffer, range, &replacement)?;
+            replace.replaced_count += 1;
+        }
+
+        ctx.queue.emit("buffer-modified", "");
+        ctx.queue.push("replace-regex-step");
+
+        Ok(())
+    });
+
+    cr.register("replace-regex-quit", |_opt, ctx| {
+        if let Some(replace) = &mut ctx.state.interactive_replace {
+            replace.pending.clear();
+        }
+        ctx.queue.push("replace-regex-step");
+        Ok(())
+    });
+
+    // Previews the next pending replacement, or wraps up the interactive
+    // replace when there are none left.
+    cr.register("replace-regex-step", replace_regex_step);
+}
+
+fn replace_regex_step(_opt: &str, ctx: ExecuteCommandContext) -> Result<(), String> {
+    let Some(replace) = &ctx.state.interactive_replace else {
+        return Ok(());
+    };
+    let buffer_handle = ctx.resources.views.get(replace.view).buffer;
+    let buffer = ctx.resources.buffers.get_mut(buffer_handle);
+
+    let Some((range, replacement)) = replace.pending.front() else {
+        let message = replacement_count_message(replace.replaced_count);
+        ctx.queue.set_state("mode", &replace.previous_mode);
+        ctx.queue.push("history-save");
+        ctx.state.modeline.set_message(message);
+        ctx.state.interactive_replace = None;
+        return Ok(());
+    };
+
+    let start = buffer
+        .map_byte_index_to_position(range.start, false)
+        .ok_or("replacement out of bounds")?;
+    let end = if range.is_empty() {
+        start
+    } else {
+        buffer
+            .map_byte_index_to_position(range.end, true)
+            .ok_or("replacement out of bounds")?
+    };
+    let selection = Selection::new().with_anchor(start).with_cursor(end);
+    *buffer.view_selections_mut(replace.view).unwrap() = Selections::new_with(selection, &[]);
+
+    ctx.state.modeline.set_message(format!(
+        "replace with '{replacement}'? (y)es (n)o (a)ll (q)uit"
+    ));
+    ctx.queue.emit("selections-modified", "");
+
+    Ok(())
+}
+
+/// Splits `/pattern/replacement/` into its pattern and replacement, so that
+/// both may contain spaces. Any ASCII punctuation can delimit them instead of
+/// `/`, and is escaped by a backslash within them. The closing delimiter is
+/// optional.
+fn parse_substitution(src: &str) -> Option<(String, String)> {
+    let mut chars = src.chars();
+    let delimiter = chars
+        .next()
+        .filter(|ch| ch.is_ascii_punctuation() && *ch != '\\')?;
+
+    let mut parts = vec![String::new()];
+    let mut escaped = false;
+    for ch in chars {
+        let part = parts.last_mut().expect("there is at least one part");
+        if escaped {
+            if ch != delimiter {
+                part.push('\\');
+            }
+            part.push(ch);
+            escaped = false;
+        } else if ch == '\\' {
+            escaped = true;
+        } else if ch == delimiter {
+            parts.push(String::new());
+        } else {
+            part.push(ch);
+        }
+    }
+    if escaped {
+        parts
+            .last_mut()
+            .expect("there is at least one part")
+            .push('\\');
+    }
+
+    if parts.len() == 3 && parts[2].is_empty() {
+        parts.pop();
+    }
+    match <[String; 2]>::try_from(parts) {
+        Ok([pattern, replacement]) if !pattern.is_empty() => Some((pattern, replacement)),
+        _ => None,
+    }
+}
+
+/// The byte range covered by a selection in the buffer's content.
+fn selection_byte_range(buffer: &TextBuffer, selection: &Selection) -> Option<Range<usize>> {
+    let start = buffer.map_position_to_byte_index(selection.start())?;
+    let text = buffer.selection_text(selection)?;
+    Some(start..start + text.len())
+}
+
+/// Finds the matches of `regex` within each of the `scopes`, along with their
+/// replacement text, expanded from `template` (`$1`, `${name}`, ...).
+fn collect_replacements(
+    buffer: &TextBuffer,
+    regex: &Regex,
+    template: &str,
+    scopes: &[Range<usize>],
+) -> Vec<(Range<usize>, String)> {
+    let content = buffer.content_to_string();
+    let mut replacements = Vec::new();
+    for scope in scopes {
+        let Some(text) = content.get(scope.clone()) else {
+            continue;
+        };
+        for caps in regex.captures_iter(text) {
+            let matsh = caps.get(0).expect("group 0 cannot fail");
+            let mut replacement = String::new();
+            caps.expand(template, &mut replacement);
+            let range = scope.start + matsh.start()..scope.start + matsh.end();
+            replacements.push((range, replacement));
+        }
+    }
+    // Overlapping selections could yield the same match more than once.
+    replacements.sort_by_key(|(range, _)| (range.start, range.end));
+    replacements.dedup_by(|a, b| a.0 == b.0 || a.0.start < b.0.end);
+    replacements
+}
+
+fn apply_replacement(
+    buffer: &mut TextBuffer,
+    range: Range<usize>,
+    replacement: &str,
+) -> Result<(), String> {
+    let start = buffer
+        .map_byte_index_to_position(range.start, false)
+        .ok_or("replacement out of bounds")?;
+    if !range.is_empty() {
+        let end = buffer
+            .map_byte_index_to_position(range.end, true)
+            .ok_or("replacement out of bounds")?;
+        buffer.delete_selection(&Selection::new().with_anchor(start).with_cursor(end))?;
+    }
+    if !replacement.is_empty() {
+        buffer.insert_str_at(start, replacement)?;
+    }
+    Ok(())
+}
+
+fn replacement_count_message(count: usize) -> String {
+    match count {
+        1 => "1 replacement".to_string(),
+        _ => format!("{count} replacements"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_utils::TestEditor;
+
+    #[test]
+    fn parses_substitutions() {
+        let parse = |src| parse_substitution(src).unwrap();
+        assert_eq!(parse("/a b/c d/"), ("a b".into(), "c d".into()));
+        assert_eq!(parse("/a/"), ("a".into(), "".into()));
+        assert_eq!(parse(r"/a\/b/c/"), ("a/b".into(), "c".into()));
+        assert_eq!(parse(r"|\d+\||x|"), (r"\d+|".into(), "x".into()));
+        assert_eq!(parse(r"/\d/$1"), (r"\d".into(), "$1".into()));
+
+        assert_eq!(parse_substitution(""), None);
+        assert_eq!(parse_substitution("a/b/"), None);
+        assert_eq!(parse_substitution("//b/"), None);
+        assert_eq!(parse_substitution("/a"), None);
+        assert_eq!(parse_substitution("/a/b/c"), None);
+    }
+
+    #[test]
+    fn replaces_with_capture_groups() {
+        let mut editor = TestEditor::new("a.txt", "let a = 1;", "0:0-0:9");
+        editor
+            .run(r"replace-regex /let (?<name>\w+) = (\d+)/const ${name}: i32 = $2/")
+            .unwrap();
+        assert_eq!(editor.text(), "const a: i32 = 1;");
+    }
+
+    #[test]
+    fn replaces_within_each_selection() {
+        let mut editor = TestEditor::new("a.txt", "a a\na a\na a", "0:0-0:0 1:0-1:2 2:2-2:2");
+        editor.run("replace-regex /a/b c/").unwrap();
+        assert_eq!(editor.text(), "b c a\nb c b c\na b c");
+
+        let mut editor = TestEditor::new("a.txt", "a a\na a", "0:0-0:0");
+        editor.run("replace-regex --buffer /a/b/").unwrap();
+        assert_eq!(editor.text(), "b b\nb b");
+    }
+
+    #[test]
+    fn fails_without_match() {
+        let mut editor = TestEditor::new("a.txt", "abc", "0:0-0:2");
+        assert!(editor.run("replace-regex /d/e/").is_err());
+        assert!(editor.run("replace-regex a b").is_err());
+        assert!(editor.run("replace-regex /(/e/").is_err());
+        assert_eq!(editor.text(), "abc");
+    }
+}
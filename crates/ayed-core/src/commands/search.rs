@@ -0,0 +1,186 @@
+use crate::{
+    command::{
+        CommandRegistry, ExecuteCommandContext, helpers::focused_buffer_command, options::Options,
+    },
+    panels::FocusedPanel,
+    state::{SearchOrigin, find_search_match},
+};
+
+pub fn register_search_commands(cr: &mut CommandRegistry) {
+    cr.register("search", |opt, ctx| {
+        let opts = Options::new()
+            .flag("reversed")
+            .flag("case-insensitive")
+            .flag("literal")
+            .parse(opt)?;
+
+        let Some(view_handle) = ctx.state.active_editor_view else {
+            return Err("no active editor view".to_string());
+        };
+        let buffer_handle = ctx.resources.views.get(view_handle).buffer;
+        let buffer = ctx.resources.buffers.get(buffer_handle);
+        let selections = buffer.view_selections(view_handle).unwrap().clone();
+
+        let search = &mut ctx.state.search;
+        search.reversed = opts.contains("reversed");
+        search.case_insensitive = opts.contains("case-insensitive");
+        search.literal = opts.contains("literal");
+        search.origin = Some(SearchOrigin {
+            selections,
+            regex: search.regex.clone(),
+        });
+
+        ctx.queue.push("panel-focus search");
+
+        Ok(())
+    });
+
+    // Moves the primary selection of the active editor to the first match of
+    // the search prompt's content, as it is being typed.
+    cr.register("search-update", |_opt, ctx| {
+        let FocusedPanel::Modeline(prompt_view_handle) = ctx.state.focused_panel else {
+            return Ok(());
+        };
+        let Some(origin) = &ctx.state.search.origin else {
+            return Ok(());
+        };
+        let Some(view_handle) = ctx.state.active_editor_view else {
+            return Ok(());
+        };
+
+        let prompt_buffer_handle = ctx.resources.views.get(prompt_view_handle).buffer;
+        let pattern = ctx
+            .resources
+            .buffers
+            .get(prompt_buffer_handle)
+            .first_line()
+            .to_string();
+
+        let view_rect = ctx.state.active_editor_view_rect(ctx.resources);
+        let view = ctx.resources.views.get_mut(view_handle);
+        let buffer = ctx.resources.buffers.get_mut(view.buffer);
+        let mut selections = origin.selections.clone();
+
+        let regex = if pattern.is_empty() {
+            None
+        } else {
+            match ctx.state.search.build_regex(&pattern) {
+                Ok(regex) => Some(regex),
+                Err(err) => {
+                    // The pattern is likely incomplete while it is being typed.
+                    ctx.state.modeline.set_error(err, &ctx.state.config);
+                    None
+                }
+            }
+        };
+
+        if let Some(regex) = &regex {
+            let reversed = ctx.state.search.reversed;
+            let primary = selections.primary();
+            let from = if reversed {
+                primary.start()
+            } else {
+                primary.cursor
+            };
+            if let Some(found) = find_search_match(buffer, regex, from, reversed, true) {
+                selections.primary_selection = found.selection;
+            }
+        }
+        let cursor = selections.primary().cursor;
+        *buffer.view_selections_mut(view_handle).unwrap() = selections;
+        ctx.state.search.regex = regex;
+
+        // The prompt is focused, so the editor's view has to be scrolled here.
+        let offset = view_rect.offset_from_position(cursor);
+        view.top_left = view.top_left.offset(offset);
+
+        Ok(())
+    });
+
+    cr.register("search-confirm", |_opt, ctx| {
+        let FocusedPanel::Modeline(prompt_view_handle) = ctx.state.focused_panel else {
+            return Err("search prompt not focused".to_string());
+        };
+        let Some(origin) = ctx.state.search.origin.take() else {
+            return Err("no search in progress".to_string());
+        };
+
+        // An empty prompt keeps the previous search around for `search-next`.
+        let prompt_buffer_handle = ctx.resources.views.get(prompt_view_handle).buffer;
+        if ctx
+            .resources
+            .buffers
+            .get(prompt_buffer_handle)
+            .first_line()
+            .is_empty()
+        {
+            ctx.state.search.regex = origin.regex;
+        }
+
+        ctx.queue.push("panel-focus editor");
+        ctx.queue.emit("selections-modified", "");
+
+        Ok(())
+    });
+
+    cr.register("search-cancel", |_opt, ctx| {
+        if let Some(origin) = ctx.state.search.origin.take()
+            && let Some(view_handle) = ctx.state.active_editor_view
+        {
+            let buffer_handle = ctx.resources.views.get(view_handle).buffer;
+            let buffer = ctx.resources.buffers.get_mut(buffer_handle);
+            *buffer.view_selections_mut(view_handle).unwrap() = origin.selections;
+            ctx.state.search.regex = origin.regex;
+        }
+
+        ctx.queue.push("panel-focus editor");
+        ctx.queue.emit("selections-modified", "");
+
+        Ok(())
+    });
+
+    cr.register("search-clear", |_opt, ctx| {
+        ctx.state.search.regex = None;
+        Ok(())
+    });
+
+    cr.register("search-next", search_next_command(false));
+    cr.register("search-previous", search_next_command(true));
+}
+
+fn search_next_command(
+    previous: bool,
+) -> impl Fn(&str, ExecuteCommandContext) -> Result<(), String> {
+    focused_buffer_command(move |opt, ctx| {
+        let opts = Options::new().flag("reversed").parse(opt)?;
+        let Some(regex) = &ctx.state.search.regex else {
+            return Err("no search pattern".to_string());
+        };
+        // Reversing an already reversed search goes forward.
+        let backward = previous ^ opts.contains("reversed") ^ ctx.state.search.reversed;
+
+        let primary = ctx.selections.primary();
+        let from = if backward {
+            primary.start()
+        } else {
+            primary.end()
+        };
+        let Some(found) = find_search_match(ctx.buffer, regex, from, backward, false) else {
+            return Err(format!("no match for '{regex}'"));
+        };
+
+        let sels = ctx.buffer.view_selections_mut(ctx.view_handle).unwrap();
+        sels.primary_selection = found.selection;
+
+        if found.wrapped {
+            let edge = if backward { "bottom" } else { "top" };
+            ctx.state
+                .modeline
+                .set_message(format!("search wrapped around to the {edge}"));
+        }
+
+        ctx.queue.emit("selections-modified", "");
+
+        Ok(())
+    })
+}
@@ -0,0 +1,294 @@
+use std::{cmp::Ordering, collections::HashSet};
+
+use crate::{
+    command::{
+        CommandRegistry,
+        helpers::{FocusedBufferCommandContext, focused_buffer_command},
+        options::Options,
+    },
+    position::Position,
+    selection::{Selection, Selections},
+};
+
+pub fn register_sort_commands(cr: &mut CommandRegistry) {
+    cr.register(
+        "sort",
+        focused_buffer_command(|opt, mut ctx| {
+            let opts = Options::new()
+                .flag("reverse")
+                .flag("numeric")
+                .flag("case-insensitive")
+                .flag("unique")
+                .parse(opt)?;
+            let numeric = opts.contains("numeric");
+            let case_insensitive = opts.contains("case-insensitive");
+
+            reorder_texts(&mut ctx, |mut texts| {
+                texts.sort_by(|a, b| compare(a, b, numeric, case_insensitive));
+                if opts.contains("unique") {
+                    texts.dedup_by(|a, b| compare(a, b, numeric, case_insensitive).is_eq());
+                }
+                if opts.contains("reverse") {
+                    texts.reverse();
+                }
+                texts
+            })
+        }),
+    );
+
+    cr.register(
+        "reverse",
+        focused_buffer_command(|_opt, mut ctx| {
+            reorder_texts(&mut ctx, |mut texts| {
+                texts.reverse();
+                texts
+            })
+        }),
+    );
+
+    cr.register(
+        "unique",
+        focused_buffer_command(|opt, mut ctx| {
+            let opts = Options::new().flag("case-insensitive").parse(opt)?;
+            let case_insensitive = opts.contains("case-insensitive");
+
+            reorder_texts(&mut ctx, |texts| {
+                let mut seen = HashSet::new();
+                texts
+                    .into_iter()
+                    .filter(|text| {
+                        let key = if case_insensitive {
+                            text.to_lowercase()
+                        } else {
+                            text.clone()
+                        };
+                        seen.insert(key)
+                    })
+                    .collect()
+            })
+        }),
+    );
+
+    cr.register(
+        "shuffle",
+        focused_buffer_command(|opt, mut ctx| {
+            // The seed makes the order reproducible.
+            let mut rng = match opt.trim() {
+                "" => XorShift::from_time(),
+                seed => XorShift::new(seed.parse().map_err(|_| format!("invalid seed '{seed}'"))?),
+            };
+            reorder_texts(&mut ctx, |mut texts| {
+                // Fisher-Yates
+                for i in (1..texts.len()).rev() {
+                    let j = rng.next() as usize % (i + 1);
+                    texts.swap(i, j);
+                }
+                texts
+            })
+        }),
+    );
+}
+
+/// Applies `f` to the lines covered by the selection when there is only one,
+/// otherwise to the contents of the selections, in buffer order. When `f`
+/// returns fewer texts, the lines or selections left over are removed.
+fn reorder_texts(
+    ctx: &mut FocusedBufferCommandContext,
+    f: impl FnOnce(Vec<String>) -> Vec<String>,
+) -> Result<(), String> {
+    if ctx.selections.count() == 1 {
+        reorder_lines(ctx, f)?;
+    } else {
+        reorder_selections(ctx, f)?;
+    }
+
+    ctx.queue.emit("buffer-modified", "");
+    ctx.queue.emit("selections-modified", "");
+    ctx.queue.push("history-save");
+
+    Ok(())
+}
+
+fn reorder_lines(
+    ctx: &mut FocusedBufferCommandContext,
+    f: impl FnOnce(Vec<String>) -> Vec<String>,
+) -> Result<(), String> {
+    let (start, end) = ctx.selections.primary().start_end();
+    let lines = (start.row..=end.row)
+        .filter_map(|row| ctx.buffer.line(row))
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+
+    // The selection covers the line break of the last line, if any, so that
+    // removed lines don't leave an empty line behind.
+    let last_line_end = ctx.buffer.line_char_count(end.row).unwrap_or(0);
+    let lines_selection = Selection::new()
+        .with_anchor(Position::new(0, start.row))
+        .with_cursor(Position::new(last_line_end, end.row));
+    let had_line_break = end.row != ctx.buffer.last_row();
+
+    let mut text = f(lines).join("\n");
+    if had_line_break {
+        text.push('\n');
+    }
+
+    *ctx.buffer.view_selections_mut(ctx.view_handle).unwrap() =
+        Selections::new_with(lines_selection, &[]);
+    ctx.buffer
+        .replace_view_selections_text(ctx.view_handle, &[Some(text)])
+}
+
+fn reorder_selections(
+    ctx: &mut FocusedBufferCommandContext,
+    f: impl FnOnce(Vec<String>) -> Vec<String>,
+) -> Result<(), String> {
+    let mut order = (0..ctx.selections.count()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| ctx.selections.get(i).unwrap().start());
+
+    let texts = order
+        .iter()
+        .map(|&i| {
+            let sel = ctx.selections.get(i).unwrap();
+            ctx.buffer.selection_text(&sel).unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+    let new_texts = f(texts);
+
+    let mut texts_by_index = vec![None; order.len()];
+    for (nth, &i) in order.iter().enumerate() {
+        texts_by_index[i] = Some(new_texts.get(nth).cloned().unwrap_or_default());
+    }
+    ctx.buffer
+        .replace_view_selections_text(ctx.view_handle, &texts_by_index)?;
+
+    // Selections whose text was removed are dropped.
+    let removed = order[new_texts.len().min(order.len())..].to_vec();
+    if !removed.is_empty() {
+        let selections = ctx.buffer.view_selections(ctx.view_handle).unwrap();
+        let mut kept = (0..selections.count())
+            .filter(|i| !removed.contains(i))
+            .map(|i| selections.get(i).unwrap())
+            .collect::<Vec<_>>();
+        if kept.is_empty() {
+            kept.push(selections.primary().shrunk_to_cursor());
+        }
+        let primary = kept.remove(0);
+        *ctx.buffer.view_selections_mut(ctx.view_handle).unwrap() =
+            Selections::new_with(primary, &kept);
+    }
+
+    Ok(())
+}
+
+fn compare(a: &str, b: &str, numeric: bool, case_insensitive: bool) -> Ordering {
+    if numeric {
+        // Like `sort --numeric-sort`, text that doesn't start with a number
+        // sorts as zero.
+        let a = leading_number(a).unwrap_or(0.0);
+        let b = leading_number(b).unwrap_or(0.0);
+        a.total_cmp(&b)
+    } else if case_insensitive {
+        a.to_lowercase().cmp(&b.to_lowercase())
+    } else {
+        a.cmp(b)
+    }
+}
+
+fn leading_number(s: &str) -> Option<f64> {
+    let s = s.trim_start();
+    let len = s
+        .char_indices()
+        .find(|&(i, ch)| !(ch.is_ascii_digit() || ch == '.' || (i == 0 && ch == '-')))
+        .map_or(s.len(), |(i, _)| i);
+    s[..len].parse().ok()
+}
+
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        // Zero is the only state that never changes.
+        Self(seed.max(1))
+    }
+
+    fn from_time() -> Self {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default();
+        Self::new(nanos)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::test_utils::TestEditor;
+
+    fn reorder(content: &str, selections: &str, command: &str) -> (String, String) {
+        let mut editor = TestEditor::new("a.txt", content, selections);
+        editor.run(command).unwrap();
+        (editor.text(), editor.selections())
+    }
+
+    #[test]
+    fn reorders_lines() {
+        let (text, _) = reorder("b\na\nc", "0:0-2:0", "sort");
+        assert_eq!(text, "a\nb\nc");
+
+        let (text, _) = reorder("1\n2\n3", "0:0-2:0", "reverse");
+        assert_eq!(text, "3\n2\n1");
+
+        let (text, _) = reorder("10\n9\n-1\nx", "0:0-3:0", "sort --numeric --reverse");
+        assert_eq!(text, "10\n9\nx\n-1");
+
+        let (text, _) = reorder("B\na\nb\nA", "0:0-3:0", "unique --case-insensitive");
+        assert_eq!(text, "B\na");
+    }
+
+    #[test]
+    fn keeps_the_line_break_of_the_last_line() {
+        let (text, sels) = reorder("b\na\nz", "0:0-1:0", "sort");
+        assert_eq!(text, "a\nb\nz");
+        assert_eq!(sels, "0:0-1:1");
+
+        let (text, _) = reorder("a\na\n\nz", "0:0-2:0", "unique");
+        assert_eq!(text, "a\n\nz");
+    }
+
+    #[test]
+    fn reorders_selections() {
+        let words = "0:0-0:0 0:2-0:3 0:5-0:5";
+        let (text, _) = reorder("c bb a", words, "sort");
+        assert_eq!(text, "a bb c");
+
+        let (text, _) = reorder("c bb a", words, "reverse");
+        assert_eq!(text, "a bb c");
+
+        // The selections whose text is removed are dropped.
+        let (text, sels) = reorder("a b a", "0:0-0:0 0:2-0:2 0:4-0:4", "unique");
+        assert_eq!(text, "a b ");
+        assert_eq!(sels, "0:0-0:0 0:2-0:2");
+    }
+
+    #[test]
+    fn shuffles_reproducibly_with_a_seed() {
+        let content = "1\n2\n3\n4\n5\n6\n7\n8";
+        let (text, _) = reorder(content, "0:0-7:0", "shuffle 7");
+        assert_ne!(text, content);
+        let mut lines = text.split('\n').collect::<Vec<_>>();
+        lines.sort();
+        assert_eq!(lines.join("\n"), content);
+
+        assert_eq!(reorder(content, "0:0-7:0", "shuffle 7").0, text);
+
+        let mut editor = TestEditor::new("a.txt", content, "0:0-7:0");
+        assert!(editor.run("shuffle x").is_err());
+    }
+}
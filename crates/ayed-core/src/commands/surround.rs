@@ -0,0 +1,419 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::{
+    command::{CommandRegistry, helpers::focused_buffer_command},
+    position::Position,
+    selection::Selection,
+    state::{TextBuffer, find_enclosing_bracket_pair},
+};
+
+static RE_TAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<(/?)([A-Za-z][\w:.-]*)[^<>]*?(/?)>").unwrap());
+
+pub fn register_surround_commands(cr: &mut CommandRegistry) {
+    cr.register(
+        "surround-add",
+        focused_buffer_command(|opt, ctx| {
+            let args = split_delimiter_args(opt);
+            let (open, close) = match args.as_slice() {
+                [open] => {
+                    let close = closing_delimiter(open, ctx.state.config.get_brackets());
+                    (open.to_string(), close)
+                }
+                [open, close] => (open.to_string(), close.to_string()),
+                _ => return Err("usage: surround-add <open> [close]".to_string()),
+            };
+
+            let mut edits = Vec::new();
+            for sel in ctx.selections.iter() {
+                let start = sel.start();
+                let end = sel.end();
+                let past_end = if end >= ctx.buffer.end_position() {
+                    ctx.buffer.end_position()
+                } else {
+                    ctx.buffer.move_position_horizontally(end, 1).unwrap_or(end)
+                };
+                edits.push(DelimiterEdit::insert(start, &open, true));
+                edits.push(DelimiterEdit::insert(past_end, &close, false));
+            }
+            apply_delimiter_edits(ctx.buffer, edits)?;
+
+            ctx.queue.emit("buffer-modified", "");
+            ctx.queue.emit("selections-modified", "");
+
+            Ok(())
+        }),
+    );
+
+    cr.register(
+        "surround-delete",
+        focused_buffer_command(|opt, ctx| {
+            let args = split_delimiter_args(opt);
+            let delimiter = match args.as_slice() {
+                [] => None,
+                [delimiter] => Some(Delimiter::parse(
+                    delimiter,
+                    ctx.state.config.get_brackets(),
+                )?),
+                _ => return Err("usage: surround-delete [delimiter]".to_string()),
+            };
+
+            let mut edits = Vec::new();
+            for sel in ctx.selections.iter() {
+                let brackets = ctx.state.config.get_brackets();
+                let Some((open, close)) = find_surrounding(ctx.buffer, sel, &delimiter, brackets)
+                else {
+                    continue;
+                };
+                edits.push(DelimiterEdit::replace(open, "", true));
+                edits.push(DelimiterEdit::replace(close, "", false));
+            }
+            if edits.is_empty() {
+                return Err("no surrounding delimiters found".to_string());
+            }
+            apply_delimiter_edits(ctx.buffer, edits)?;
+
+            ctx.queue.emit("buffer-modified", "");
+            ctx.queue.emit("selections-modified", "");
+
+            Ok(())
+        }),
+    );
+
+    cr.register(
+        "surround-replace",
+        focused_buffer_command(|opt, ctx| {
+            let brackets = ctx.state.config.get_brackets();
+            let args = split_delimiter_args(opt);
+            let (new_open, old_delimiter) = match args.as_slice() {
+                [new] => (new.to_string(), None),
+                [new, old] => (new.to_string(), Some(Delimiter::parse(old, brackets)?)),
+                _ => return Err("usage: surround-replace <new> [old]".to_string()),
+            };
+            let new_close = closing_delimiter(&new_open, brackets);
+
+            let mut edits = Vec::new();
+            for sel in ctx.selections.iter() {
+                let Some((open, close)) =
+                    find_surrounding(ctx.buffer, sel, &old_delimiter, brackets)
+                else {
+                    continue;
+                };
+                edits.push(DelimiterEdit::replace(open, &new_open, true));
+                edits.push(DelimiterEdit::replace(close, &new_close, false));
+            }
+            if edits.is_empty() {
+                return Err("no surrounding delimiters found".to_string());
+            }
+            apply_delimiter_edits(ctx.buffer, edits)?;
+
+            ctx.queue.emit("buffer-modified", "");
+            ctx.queue.emit("selections-modified", "");
+
+            Ok(())
+        }),
+    );
+}
+
+enum Delimiter {
+    Pair(char, char),
+    /// An empty name matches any tag.
+    Tag(String),
+}
+
+impl Delimiter {
+    fn parse(src: &str, brackets: &[(char, char)]) -> Result<Self, String> {
+        if let Some(inner) = src.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+            let name = inner.trim_start_matches('/').split_whitespace().next();
+            return Ok(Self::Tag(name.unwrap_or_default().to_string()));
+        }
+
+        let mut chars = src.chars();
+        match (chars.next(), chars.next()) {
+            (Some(ch), None) => {
+                let pair = brackets
+                    .iter()
+                    .find(|(open, close)| *open == ch || *close == ch)
+                    .copied()
+                    .unwrap_or((ch, ch));
+                Ok(Self::Pair(pair.0, pair.1))
+            }
+            _ => Err(format!("unsupported delimiter '{src}'")),
+        }
+    }
+}
+
+struct DelimiterEdit {
+    at: Position,
+    delete: Option<Selection>,
+    insert: String,
+    is_open: bool,
+}
+
+impl DelimiterEdit {
+    fn insert(at: Position, text: &str, is_open: bool) -> Self {
+        Self {
+            at,
+            delete: None,
+            insert: text.to_string(),
+            is_open,
+        }
+    }
+
+    fn replace(delete: Selection, text: &str, is_open: bool) -> Self {
+        Self {
+            at: delete.start(),
+            delete: Some(delete),
+            insert: text.to_string(),
+            is_open,
+        }
+    }
+}
+
+/// Applies the edits from the end of the buffer to its start, so that the
+/// positions of the edits that are yet to be applied stay valid.
+/// The buffer takes care of keeping the selections consistent.
+fn apply_delimiter_edits(
+    buffer: &mut TextBuffer,
+    mut edits: Vec<DelimiterEdit>,
+) -> Result<(), String> {
+    // When an opening and closing delimiter are inserted at the same position
+    // (ex: adjacent selections), the opening one must be inserted first so that
+    // it ends up after the closing one.
+    edits.sort_by(|a, b| b.at.cmp(&a.at).then(b.is_open.cmp(&a.is_open)));
+    edits.dedup_by(|a, b| a.at == b.at && a.delete.is_some() && b.delete.is_some());
+
+    for edit in edits {
+        if let Some(delete) = edit.delete {
+            buffer.delete_selection(&delete)?;
+        }
+        if !edit.insert.is_empty() {
+            buffer.insert_str_at(edit.at, &edit.insert)?;
+        }
+    }
+    Ok(())
+}
+
+/// Finds the delimiters surrounding the selection, returned as the regions
+/// covered by the opening and the closing delimiters.
+/// When no delimiter is specified, the nearest of the configured brackets or
+/// tags is used.
+fn find_surrounding(
+    buffer: &TextBuffer,
+    sel: &Selection,
+    delimiter: &Option<Delimiter>,
+    brackets: &[(char, char)],
+) -> Option<(Selection, Selection)> {
+    let (start, end) = sel.start_end();
+    let pair_to_selections = |(open, close): (Position, Position)| {
+        (
+            Selection::with_position(open),
+            Selection::with_position(close),
+        )
+    };
+
+    match delimiter {
+        Some(Delimiter::Pair(open, close)) => {
+            find_enclosing_bracket_pair(buffer, start, end, *open, *close).map(pair_to_selections)
+        }
+        Some(Delimiter::Tag(name)) => find_enclosing_tag_pair(buffer, start, end, name),
+        None => brackets
+            .iter()
+            .flat_map(|&(open, close)| find_enclosing_bracket_pair(buffer, start, end, open, close))
+            .map(pair_to_selections)
+            .chain(find_enclosing_tag_pair(buffer, start, end, ""))
+            .max_by_key(|(open, _)| open.start()),
+    }
+}
+
+fn find_enclosing_tag_pair(
+    buffer: &TextBuffer,
+    start: Position,
+    end: Position,
+    name: &str,
+) -> Option<(Selection, Selection)> {
+    let content = buffer.content_to_string();
+    let start_idx = buffer.map_position_to_byte_index(start)?;
+    let end_idx = buffer.map_position_to_byte_index(end)?;
+
+    // Pair up opening and closing tags, from the start of the buffer.
+    let mut open_tags: Vec<(&str, std::ops::Range<usize>)> = Vec::new();
+    let mut best: Option<(std::ops::Range<usize>, std::ops::Range<usize>)> = None;
+    for caps in RE_TAG.captures_iter(&content) {
+        let range = caps.get(0).expect("group 0 cannot fail").range();
+        let is_closing = !caps[1].is_empty();
+        let is_self_closing = !caps[3].is_empty();
+        let tag_name = caps.get(2).expect("tag name is not optional").as_str();
+        if is_self_closing {
+            continue;
+        }
+        if !is_closing {
+            open_tags.push((tag_name, range));
+            continue;
+        }
+
+        let Some(open_idx) = open_tags.iter().rposition(|(n, _)| *n == tag_name) else {
+            continue;
+        };
+        let (_, open_range) = open_tags.remove(open_idx);
+        open_tags.truncate(open_idx);
+
+        let encloses = open_range.start <= start_idx && range.end > end_idx;
+        let name_matches = name.is_empty() || name == tag_name;
+        let is_nearer = best
+            .as_ref()
+            .is_none_or(|(best_open, _)| best_open.start < open_range.start);
+        if encloses && name_matches && is_nearer {
+            best = Some((open_range, range));
+        }
+    }
+
+    let to_selection = |range: std::ops::Range<usize>| -> Option<Selection> {
+        let from = buffer.map_byte_index_to_position(range.start, false)?;
+        let to = buffer.map_byte_index_to_position(range.end, true)?;
+        Some(Selection::new().with_anchor(from).with_cursor(to))
+    };
+    let (open, close) = best?;
+    Some((to_selection(open)?, to_selection(close)?))
+}
+
+/// Computes the closing delimiter from an opening one. Tags are closed by their
+/// closing tag, otherwise the opening delimiter is mirrored (ex: `{(` gives `)}`).
+fn closing_delimiter(open: &str, brackets: &[(char, char)]) -> String {
+    if let Some(caps) = RE_TAG.captures(open)
+        && caps.get(0).is_some_and(|m| m.as_str() == open)
+    {
+        return format!("</{}>", &caps[2]);
+    }
+
+    open.chars()
+        .rev()
+        .map(|ch| {
+            brackets
+                .iter()
+                .find(|(o, _)| *o == ch)
+                .map(|(_, c)| *c)
+                .unwrap_or(ch)
+        })
+        .collect()
+}
+
+/// Splits the arguments on whitespace, except inside of tags, so that
+/// `<div class="a"> </div>` gives two arguments.
+fn split_delimiter_args(opt: &str) -> Vec<&str> {
+    let mut args = Vec::new();
+    let mut rest = opt.trim();
+    while !rest.is_empty() {
+        let end = if rest.starts_with('<') {
+            rest.find('>').map(|idx| idx + 1)
+        } else {
+            None
+        }
+        .or_else(|| rest.find(char::is_whitespace))
+        .unwrap_or(rest.len());
+        args.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::test_utils::TestEditor;
+
+    fn surround(content: &str, selections: &str, command: &str) -> (String, String) {
+        let mut editor = TestEditor::new("a.txt", content, selections);
+        editor.run(command).unwrap();
+        (editor.text(), editor.selections())
+    }
+
+    #[test]
+    fn adds_around_each_selection() {
+        let (text, sels) = surround("a bc d", "0:0-0:0 0:2-0:3", "surround-add (");
+        assert_eq!(text, "(a) (bc) d");
+        assert_eq!(sels, "0:1-0:1 0:5-0:6");
+
+        let (text, _) = surround("ab", "0:0-0:0 0:1-0:1", "surround-add {[");
+        assert_eq!(text, "{[a]}{[b]}");
+
+        let (text, _) = surround("a", "0:0-0:0", "surround-add ' '");
+        assert_eq!(text, "'a'");
+    }
+
+    #[test]
+    fn adds_tags() {
+        let (text, _) = surround("a b", "0:2-0:2", r#"surround-add <div class="x y">"#);
+        assert_eq!(text, r#"a <div class="x y">b</div>"#);
+
+        let (text, _) = surround("a", "0:0-0:0", "surround-add <b> </strong>");
+        assert_eq!(text, "<b>a</strong>");
+    }
+
+    #[test]
+    fn deletes_the_nearest_delimiters() {
+        let (text, _) = surround("((a)) [b]", "0:2-0:2 0:7-0:7", "surround-delete");
+        assert_eq!(text, "(a) b");
+
+        let (text, _) = surround("(a [b c] d)", "0:4-0:4", "surround-delete (");
+        assert_eq!(text, "a [b c] d");
+    }
+
+    #[test]
+    fn deletes_a_pair_shared_by_selections_once() {
+        let (text, sels) = surround("(a b)", "0:1-0:1 0:3-0:3", "surround-delete");
+        assert_eq!(text, "a b");
+        assert_eq!(sels, "0:0-0:0 0:2-0:2");
+    }
+
+    #[test]
+    fn deletes_same_character_quotes() {
+        let (text, _) = surround(r#""a" "b""#, "0:5-0:5", r#"surround-delete ""#);
+        assert_eq!(text, r#""a" b"#);
+
+        let (text, _) = surround("'a' 'b'", "0:1-0:1 0:5-0:5", "surround-delete '");
+        assert_eq!(text, "a b");
+    }
+
+    #[test]
+    fn deletes_tags() {
+        let content = "<p><b>x</b> <br/></p>";
+        let (text, _) = surround(content, "0:6-0:6", "surround-delete");
+        assert_eq!(text, "<p>x <br/></p>");
+
+        let (text, _) = surround(content, "0:6-0:6", "surround-delete <p>");
+        assert_eq!(text, "<b>x</b> <br/>");
+    }
+
+    #[test]
+    fn replaces_delimiters() {
+        let (text, _) = surround("(a) [b]", "0:1-0:1 0:5-0:5", "surround-replace {");
+        assert_eq!(text, "{a} {b}");
+
+        let (text, _) = surround(r#"("a")"#, "0:2-0:2", r#"surround-replace ' ""#);
+        assert_eq!(text, "('a')");
+
+        let (text, _) = surround("<b>a</b>", "0:3-0:3", "surround-replace <em> <b>");
+        assert_eq!(text, "<em>a</em>");
+    }
+
+    #[test]
+    fn fails_without_surrounding_delimiters() {
+        let mut editor = TestEditor::new("a.txt", "(a) b", "0:4-0:4");
+        assert!(editor.run("surround-delete").is_err());
+        assert!(editor.run("surround-replace [").is_err());
+        assert_eq!(editor.text(), "(a) b");
+    }
+
+    #[test]
+    fn splits_arguments_outside_of_tags() {
+        assert_eq!(split_delimiter_args(" ( ) "), ["(", ")"]);
+        assert_eq!(
+            split_delimiter_args(r#"<a href="b c"> </a>"#),
+            [r#"<a href="b c">"#, "</a>"]
+        );
+        assert!(split_delimiter_args("").is_empty());
+    }
+}
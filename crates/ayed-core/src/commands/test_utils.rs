@@ -0,0 +1,111 @@
+use crate::{
+    core::{ConfigFiles, Core},
+    input::Input,
+    selection::Selections,
+    slotmap::Handle,
+    state::{TextBuffer, View},
+    ui::Size,
+};
+
+/// An editor running the builtin commands and config on a single buffer, to
+/// test commands the way they are run by the user.
+pub struct TestEditor {
+    pub core: Core,
+}
+
+impl TestEditor {
+    /// Opens a buffer with `content` and `selections` (ex: `0:0-0:3 1:0-1:0`,
+    /// the first one being the primary selection). The `path` is never read
+    /// nor written, but selects the file-specific config.
+    pub fn new(path: &str, content: &str, selections: &str) -> Self {
+        let mut core = Core::with_builtins_and_config_files(&ConfigFiles {
+            no_user_config: true,
+            ..Default::default()
+        });
+        core.set_viewport_size(Size::new(80, 24));
+
+        let mut this = Self { core };
+        this.run(&format!("edit {path}")).unwrap();
+        this.set_content(content);
+        this.set_selections(selections);
+        this
+    }
+
+    /// Runs the `command` and those it queued, returning the error shown in
+    /// the modeline if one of them failed.
+    pub fn run(&mut self, command: &str) -> Result<(), String> {
+        self.core.state.modeline.clear_content_override();
+        self.core.queue_command(command.to_string());
+        self.core.tick();
+        match &self.core.state.modeline.content_override {
+            Some(content) if content.top_style.is_some() => Err(content.text.clone()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Presses the key of each character of `text`, through the keybinds of
+    /// the current mode.
+    pub fn type_text(&mut self, text: &str) -> Result<(), String> {
+        for ch in text.chars() {
+            self.run(&format!("map-input {}", Input::from_char(ch)))?;
+        }
+        Ok(())
+    }
+
+    pub fn text(&self) -> String {
+        self.buffer().content_to_string()
+    }
+
+    pub fn set_content(&mut self, content: &str) {
+        let buffer = self.buffer_handle();
+        let buffer = self.core.resources.buffers.get_mut(buffer);
+        buffer.lines = content.split('\n').map(str::to_string).collect();
+        // Like any edit, so that `history-save` records it.
+        buffer.history_dirty.set(true);
+    }
+
+    pub fn selections(&self) -> String {
+        self.buffer()
+            .view_selections(self.view_handle())
+            .expect("the view has selections")
+            .to_string()
+    }
+
+    /// The text of each selection, the primary one first.
+    pub fn selected_texts(&self) -> Vec<String> {
+        let buffer = self.buffer();
+        buffer
+            .view_selections(self.view_handle())
+            .expect("the view has selections")
+            .iter()
+            .map(|sel| buffer.selection_text(sel).unwrap_or_default())
+            .collect()
+    }
+
+    pub fn set_selections(&mut self, selections: &str) {
+        let view = self.view_handle();
+        let buffer = self.buffer_handle();
+        *self
+            .core
+            .resources
+            .buffers
+            .get_mut(buffer)
+            .view_selections_mut(view)
+            .expect("the view has selections") = Selections::parse(selections).unwrap();
+    }
+
+    pub fn view_handle(&self) -> Handle<View> {
+        self.core
+            .state
+            .active_editor_view
+            .expect("a view is opened")
+    }
+
+    pub fn buffer_handle(&self) -> Handle<TextBuffer> {
+        self.core.resources.views.get(self.view_handle()).buffer
+    }
+
+    pub fn buffer(&self) -> &TextBuffer {
+        self.core.resources.buffers.get(self.buffer_handle())
+    }
+}
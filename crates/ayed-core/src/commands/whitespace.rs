@@ -0,0 +1,153 @@
+use crate::{
+    command::{CommandRegistry, helpers::focused_buffer_command, options::Options},
+    position::{Column, Position},
+    selection::{Selection, Selections},
+    state::TextBuffer,
+    utils::string_utils::{char_count, ops::is_whitespace},
+};
+
+pub fn register_whitespace_commands(cr: &mut CommandRegistry) {
+    // The following commands ignore their options, so that they can be hooked
+    // to events like `buffer-before-write`, which pass the path of the buffer.
+
+    cr.register(
+        "whitespace-trim-trailing",
+        focused_buffer_command(|_opt, ctx| {
+            let mut modified = false;
+            for row in 0..ctx.buffer.line_count() {
+                let line = ctx.buffer.line(row).unwrap();
+                // The `\r` of CRLF line endings stays, only spaces and tabs are trailing.
+                let content = line.strip_suffix('\r').unwrap_or(line);
+                let content_char_count = char_count(content) as Column;
+                let trimmed_char_count =
+                    char_count(content.trim_end_matches([' ', '\t'])) as Column;
+                if trimmed_char_count == content_char_count {
+                    continue;
+                }
+
+                let trailing = Selection::new()
+                    .with_anchor(Position::new(trimmed_char_count, row))
+                    .with_cursor(Position::new(content_char_count - 1, row));
+                ctx.buffer.delete_selection(&trailing)?;
+                modified = true;
+            }
+
+            if modified {
+                clamp_selections(ctx.buffer);
+                ctx.queue.emit("buffer-modified", "");
+                ctx.queue.emit("selections-modified", "");
+                ctx.queue.push("history-save");
+            }
+
+            Ok(())
+        }),
+    );
+
+    cr.register(
+        "whitespace-ensure-final-newline",
+        focused_buffer_command(|_opt, ctx| {
+            let end = ctx.buffer.end_position();
+            if end.column == 0 {
+                return Ok(());
+            }
+
+            ctx.buffer.insert_str_at(end, "\n")?;
+            // Selections at the end of the buffer stay on their line.
+            let new_end = ctx.buffer.end_position();
+            for selection in ctx
+                .buffer
+                .selections
+                .values_mut()
+                .flat_map(Selections::iter_mut)
+            {
+                if selection.cursor == new_end {
+                    *selection = selection.with_cursor(end);
+                }
+                if selection.anchor == new_end {
+                    *selection = selection.with_anchor(end);
+                }
+            }
+
+            ctx.queue.emit("buffer-modified", "");
+            ctx.queue.emit("selections-modified", "");
+            ctx.queue.push("history-save");
+
+            Ok(())
+        }),
+    );
+
+    cr.register(
+        "__whitespace-before-write-shim",
+        focused_buffer_command(|_opt, ctx| {
+            let editor = ctx.buffer.editor_config(&ctx.state.config);
+            if editor.trim_trailing_whitespace {
+                ctx.queue.push("whitespace-trim-trailing");
+            }
+            if editor.insert_final_newline {
+                ctx.queue.push("whitespace-ensure-final-newline");
+            }
+            Ok(())
+        }),
+    );
+
+    cr.register(
+        "whitespace-retab",
+        focused_buffer_command(|opt, ctx| {
+            let opts = Options::new().flag("tabs").flag("spaces").parse(opt)?;
+            let mut editor = ctx.buffer.editor_config(&ctx.state.config);
+            editor.indent_with_tabs = match (opts.contains("tabs"), opts.contains("spaces")) {
+                (true, true) => return Err("--tabs and --spaces are exclusive".to_string()),
+                (false, false) => editor.indent_with_tabs,
+                (use_tabs, _) => use_tabs,
+            };
+
+            let mut modified = false;
+            for row in 0..ctx.buffer.line_count() {
+                let line = ctx.buffer.line(row).unwrap();
+                let indentation_len = line.len() - line.trim_start_matches(is_whitespace).len();
+                let indentation = &line[..indentation_len];
+
+                let width = editor.indentation_width(indentation);
+                let new_indentation = editor.indentation(width);
+                if new_indentation == indentation {
+                    continue;
+                }
+
+                let indentation_char_count = char_count(indentation) as Column;
+                let indentation_selection = Selection::new()
+                    .with_anchor(Position::new(0, row))
+                    .with_cursor(Position::new(indentation_char_count - 1, row));
+                ctx.buffer.delete_selection(&indentation_selection)?;
+                ctx.buffer
+                    .insert_str_at(Position::new(0, row), &new_indentation)?;
+                modified = true;
+            }
+
+            if modified {
+                clamp_selections(ctx.buffer);
+                ctx.queue.emit("buffer-modified", "");
+                ctx.queue.emit("selections-modified", "");
+                ctx.queue.push("history-save");
+            }
+
+            Ok(())
+        }),
+    );
+}
+
+/// Keeps the selections of every view within the lines, which may have been
+/// shortened.
+fn clamp_selections(buffer: &mut TextBuffer) {
+    let clamped = buffer
+        .selections
+        .iter()
+        .map(|(&view, selections)| {
+            let mut selections = selections.clone();
+            for selection in selections.iter_mut() {
+                *selection = buffer.limit_selection_to_content(selection);
+            }
+            (view, selections)
+        })
+        .collect::<Vec<_>>();
+    buffer.selections.extend(clamped);
+}
@@ -13,6 +13,11 @@ pub struct AppliedConfig {
     pub syntax: HashMap<String, Vec<Regex>>,
     pub editor: EditorConfig,
     pub theme: HashMap<String, Color>,
+    pub brackets: Vec<(char, char)>,
+    /// Opening and closing characters inserted together by auto-pairs.
+    pub pairs: Vec<(char, char)>,
+    pub comment: CommentTokens,
+    pub indent_rules: IndentRules,
 }
 
 impl AppliedConfig {
@@ -114,7 +119,8 @@ pub fn build_applied_config(modules: &Vec<ConfigModule>, state: &ConfigState) ->
         .map(|(rule_name, patterns)| {
             let regexes = patterns
                 .iter()
-                .map(|pat| Regex::new(pat).unwrap())
+                // Validated when parsing modules.
+                .flat_map(|pat| Regex::new(pat).ok())
                 .collect();
             (rule_name.to_string(), regexes)
         })
@@ -128,6 +134,68 @@ pub fn build_applied_config(modules: &Vec<ConfigModule>, state: &ConfigState) ->
             .and_then(|s| u8::from_str_radix(s, 10).ok())
         {
             editor.indent_size = indent_size as i32;
+            editor.indent_size_is_set = true;
+        }
+        if let Some(jump_threshold) = mapping
+            .get("jump-threshold")
+            .and_then(|v| v.first())
+            .and_then(|s| s.parse::<u32>().ok())
+        {
+            editor.jump_threshold = jump_threshold;
+        }
+        if let Some(indent_with_tabs) = mapping
+            .get("indent-style")
+            .and_then(|v| v.first())
+            .and_then(|s| match s.as_str() {
+                "tab" | "tabs" => Some(true),
+                "space" | "spaces" => Some(false),
+                _ => None,
+            })
+        {
+            editor.indent_with_tabs = indent_with_tabs;
+            editor.indent_style_is_set = true;
+        }
+        if let Some(detect_indentation) = mapping
+            .get("detect-indentation")
+            .and_then(|v| v.first())
+            .and_then(|s| s.parse::<bool>().ok())
+        {
+            editor.detect_indentation = detect_indentation;
+        }
+        if let Some(tab_width) = mapping
+            .get("tab-width")
+            .and_then(|v| v.first())
+            .and_then(|s| s.parse::<i32>().ok())
+        {
+            editor.tab_width = Some(tab_width);
+        }
+        if let Some(trim_trailing_whitespace) = mapping
+            .get("trim-trailing-whitespace")
+            .and_then(|v| v.first())
+            .and_then(|s| s.parse::<bool>().ok())
+        {
+            editor.trim_trailing_whitespace = trim_trailing_whitespace;
+        }
+        if let Some(insert_final_newline) = mapping
+            .get("insert-final-newline")
+            .and_then(|v| v.first())
+            .and_then(|s| s.parse::<bool>().ok())
+        {
+            editor.insert_final_newline = insert_final_newline;
+        }
+        if let Some(text_width) = mapping
+            .get("text-width")
+            .and_then(|v| v.first())
+            .and_then(|s| s.parse::<u32>().ok())
+        {
+            editor.text_width = text_width;
+        }
+        if let Some(auto_wrap) = mapping
+            .get("auto-wrap")
+            .and_then(|v| v.first())
+            .and_then(|s| s.parse::<bool>().ok())
+        {
+            editor.auto_wrap = auto_wrap;
         }
     }
 
@@ -141,21 +209,174 @@ pub fn build_applied_config(modules: &Vec<ConfigModule>, state: &ConfigState) ->
         })
         .collect();
 
+    let brackets = mappings
+        .get("brackets")
+        .unwrap_or(&HashMap::new())
+        .iter()
+        .flat_map(|(open, values)| {
+            let open = single_char(open)?;
+            let close = single_char(values.first()?)?;
+            Some((open, close))
+        })
+        .collect();
+
+    let pairs = mappings
+        .get("pairs")
+        .unwrap_or(&HashMap::new())
+        .iter()
+        .flat_map(|(open, values)| {
+            let open = single_char(open)?;
+            let close = single_char(values.first()?)?;
+            Some((open, close))
+        })
+        .collect();
+
+    let mut comment = CommentTokens::default();
+    if let Some(mapping) = mappings.get("comment") {
+        comment.line = mapping.get("line").and_then(|v| v.first()).cloned();
+        if let Some([start, end]) = mapping.get("block").map(Vec::as_slice) {
+            comment.block = Some((start.to_string(), end.to_string()));
+        }
+    }
+
+    let mut indent_rules = IndentRules::default();
+    if let Some(mapping) = mappings.get("indent-rules") {
+        let regexes = |key: &str| -> Vec<Regex> {
+            mapping
+                .get(key)
+                .unwrap_or(&Vec::new())
+                .iter()
+                .flat_map(|pat| Regex::new(pat).ok())
+                .collect()
+        };
+        indent_rules.increase = regexes("increase");
+        indent_rules.decrease = regexes("decrease");
+        indent_rules.keep = regexes("keep");
+    }
+
     AppliedConfig {
         mappings,
         syntax,
         editor,
         theme,
+        brackets,
+        pairs,
+        comment,
+        indent_rules,
+    }
+}
+
+fn single_char(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+    let ch = chars.next()?;
+    chars.next().is_none().then_some(ch)
+}
+
+#[derive(Debug, Default)]
+pub struct CommentTokens {
+    pub line: Option<String>,
+    pub block: Option<(String, String)>,
+}
+
+/// Regexes deciding the indentation of lines, matched against whole lines.
+#[derive(Debug, Default)]
+pub struct IndentRules {
+    /// Lines after a matching line are indented one more level.
+    pub increase: Vec<Regex>,
+    /// Matching lines are indented one less level.
+    pub decrease: Vec<Regex>,
+    /// Matching lines keep their indentation, and the lines after them are
+    /// indented based on the lines before them.
+    pub keep: Vec<Regex>,
+}
+
+impl IndentRules {
+    pub fn increases(&self, line: &str) -> bool {
+        self.increase.iter().any(|regex| regex.is_match(line))
+    }
+
+    pub fn decreases(&self, line: &str) -> bool {
+        self.decrease.iter().any(|regex| regex.is_match(line))
+    }
+
+    pub fn keeps(&self, line: &str) -> bool {
+        self.keep.iter().any(|regex| regex.is_match(line))
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct EditorConfig {
     pub indent_size: i32,
+    /// Whether indentation is made of tabs rather than spaces.
+    pub indent_with_tabs: bool,
+    /// Whether the indentation detected from the content of buffers overrides
+    /// the above, where the config doesn't set them.
+    pub detect_indentation: bool,
+    /// Whether a config module sets `indent-size`.
+    pub indent_size_is_set: bool,
+    /// Whether a config module sets `indent-style`.
+    pub indent_style_is_set: bool,
+    /// The displayed width of tabs, the indent size when unset.
+    pub tab_width: Option<i32>,
+    /// Moves of the primary cursor across more lines than this are recorded
+    /// in the jump list.
+    pub jump_threshold: u32,
+    /// The width that `reflow` and auto-wrap fit lines in.
+    pub text_width: u32,
+    /// Whether typing past the text width in insert mode breaks the line.
+    pub auto_wrap: bool,
+    /// Whether trailing whitespace is removed when writing buffers.
+    pub trim_trailing_whitespace: bool,
+    /// Whether a line break is added at the end of buffers when writing them.
+    pub insert_final_newline: bool,
+}
+
+impl EditorConfig {
+    pub fn tab_width(&self) -> i32 {
+        self.tab_width.unwrap_or(self.indent_size).max(1)
+    }
+
+    /// The display width of `indentation`, with tabs stopping at multiples of
+    /// the tab width.
+    pub fn indentation_width(&self, indentation: &str) -> i32 {
+        let tab_width = self.tab_width();
+        indentation.chars().fold(0, |width, ch| match ch {
+            '\t' => (width / tab_width + 1) * tab_width,
+            _ => width + 1,
+        })
+    }
+
+    /// Indentation of the given display width, made of tabs when indenting
+    /// with tabs, followed by the spaces that don't make up a whole tab.
+    pub fn indentation(&self, width: i32) -> String {
+        let width = width.max(0) as usize;
+        if self.indent_with_tabs {
+            let tab_width = self.tab_width() as usize;
+            format!(
+                "{}{}",
+                "\t".repeat(width / tab_width),
+                " ".repeat(width % tab_width)
+            )
+        } else {
+            " ".repeat(width)
+        }
+    }
 }
 
 impl Default for EditorConfig {
     fn default() -> Self {
-        Self { indent_size: 4 }
+        Self {
+            indent_size: 4,
+            indent_with_tabs: false,
+            detect_indentation: true,
+            indent_size_is_set: false,
+            indent_style_is_set: false,
+            tab_width: None,
+            jump_threshold: 10,
+            text_width: 80,
+            auto_wrap: false,
+            trim_trailing_whitespace: false,
+            insert_final_newline: false,
+        }
     }
 }
@@ -0,0 +1,186 @@
+use std::{fmt, ops::Range, path::PathBuf};
+
+/// The errors of a config module that failed to load.
+#[derive(Debug, Clone)]
+pub struct ConfigError {
+    /// The file of the module, if it was loaded from one.
+    pub path: Option<PathBuf>,
+    pub diagnostics: Vec<ConfigDiagnostic>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigDiagnostic {
+    pub message: String,
+    /// Where the error is in the source of the module, if anywhere.
+    pub location: Option<SourceLocation>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceLocation {
+    /// Byte range in the source.
+    pub span: Range<usize>,
+    /// Starts at 1.
+    pub line: usize,
+    /// Starts at 1, in characters.
+    pub column: usize,
+    /// The characters of the span on its first line.
+    pub length: usize,
+    /// The line the span starts on.
+    pub line_text: String,
+}
+
+impl ConfigError {
+    pub fn new(path: Option<PathBuf>, message: impl Into<String>) -> Self {
+        Self {
+            path,
+            diagnostics: vec![ConfigDiagnostic {
+                message: message.into(),
+                location: None,
+            }],
+        }
+    }
+
+    pub fn from_parse_errors(
+        path: Option<PathBuf>,
+        src: &str,
+        errors: &[ayed_config_parser::Error],
+    ) -> Self {
+        let diagnostics = errors
+            .iter()
+            .map(|err| ConfigDiagnostic {
+                message: err.to_string(),
+                location: Some(SourceLocation::new(src, err.span(src))),
+            })
+            .collect();
+        Self { path, diagnostics }
+    }
+
+    /// An error at `slice`, which must be part of `src`.
+    pub fn at_slice(
+        path: Option<PathBuf>,
+        src: &str,
+        slice: &str,
+        message: impl Into<String>,
+    ) -> Self {
+        let span = ayed_config_parser::slice_span(src, slice);
+        Self {
+            path,
+            diagnostics: vec![ConfigDiagnostic {
+                message: message.into(),
+                location: Some(SourceLocation::new(src, span)),
+            }],
+        }
+    }
+
+    /// The first of `errors`, with the count of the others, if there are any.
+    pub fn summary(errors: &[ConfigError]) -> Option<String> {
+        let first = errors.first()?;
+        Some(match errors.len() {
+            1 => first.to_string(),
+            count => format!("{first} (and {} more config errors)", count - 1),
+        })
+    }
+
+    fn source_name(&self) -> String {
+        match &self.path {
+            Some(path) => path.display().to_string(),
+            None => "<config>".to_string(),
+        }
+    }
+
+    /// All the diagnostics, each followed by the line of source it is on
+    /// with carets under its span.
+    pub fn report(&self) -> String {
+        let source_name = self.source_name();
+        let mut report = String::new();
+        for diagnostic in &self.diagnostics {
+            let Some(location) = &diagnostic.location else {
+                report.push_str(&format!("{source_name}: {}\n", diagnostic.message));
+                continue;
+            };
+            report.push_str(&format!(
+                "{source_name}:{}:{}: {}\n",
+                location.line, location.column, diagnostic.message
+            ));
+            report.push_str(&format!("    {}\n", location.line_text));
+            report.push_str(&format!("    {}\n", location.caret_line()));
+        }
+        report
+    }
+}
+
+impl fmt::Display for ConfigError {
+    /// The first diagnostic, with the count of the others.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source_name())?;
+        let Some(first) = self.diagnostics.first() else {
+            return write!(f, ": invalid config");
+        };
+        if let Some(location) = &first.location {
+            write!(f, ":{}:{}", location.line, location.column)?;
+        }
+        write!(f, ": {}", first.message)?;
+        if self.diagnostics.len() > 1 {
+            write!(f, " (and {} more)", self.diagnostics.len() - 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl SourceLocation {
+    fn new(src: &str, span: Range<usize>) -> Self {
+        let before = &src[..span.start];
+        let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+        let line_end = src[span.start..]
+            .find('\n')
+            .map_or(src.len(), |idx| span.start + idx);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            length: src[span.start..span.end.min(line_end)].chars().count(),
+            line_text: src[line_start..line_end].to_string(),
+            span,
+        }
+    }
+
+    /// Carets under the span, at least one. Tabs before the span are kept so
+    /// that the carets line up with the line.
+    fn caret_line(&self) -> String {
+        let indent = self
+            .line_text
+            .chars()
+            .take(self.column - 1)
+            .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+        format!("{indent}{}", "^".repeat(self.length.max(1)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_parse_errors_with_carets() {
+        let src = "editor {\n    indent-size 2\n\ttheme\n}\n";
+        let (_, errors) = ayed_config_parser::parse_module(src);
+        let err = ConfigError::from_parse_errors(Some("a.ayedconf".into()), src, &errors);
+        assert_eq!(err.to_string(), "a.ayedconf:3:7: expected an entry value");
+        assert_eq!(
+            err.report(),
+            "a.ayedconf:3:7: expected an entry value\n    \ttheme\n    \t     ^\n"
+        );
+    }
+
+    #[test]
+    fn reports_invalid_regexes() {
+        let mut config = crate::config::Config::default();
+        let err = config
+            .add_module("syntax {\n    keyword (let|fn\n}\n")
+            .unwrap_err();
+        assert_eq!(
+            err.report(),
+            "<config>:2:13: invalid regex '(let|fn'\n        keyword (let|fn\n                ^^^^^^^\n"
+        );
+    }
+}
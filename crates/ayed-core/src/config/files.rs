@@ -0,0 +1,59 @@
+use std::path::{Path, PathBuf};
+
+/// Which configuration files are loaded after the builtin ones.
+#[derive(Debug, Default, Clone)]
+pub struct ConfigFiles {
+    /// Skips the files of the user config directory.
+    pub no_user_config: bool,
+    /// Files loaded after those of the user and the project.
+    pub extra_paths: Vec<PathBuf>,
+}
+
+impl ConfigFiles {
+    /// The paths of the files to load, in order: `$XDG_CONFIG_HOME/ayed/`,
+    /// the `.ayed/` directories of `working_directory` and its ancestors,
+    /// closest last, then the extra paths. Later files take precedence.
+    pub fn paths(&self, working_directory: &Path) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+
+        if !self.no_user_config
+            && let Some(dir) = user_config_dir()
+        {
+            paths.extend(config_files_in(&dir));
+        }
+
+        let project_dirs = working_directory
+            .ancestors()
+            .map(|dir| dir.join(".ayed"))
+            .filter(|dir| dir.is_dir())
+            .collect::<Vec<_>>();
+        for dir in project_dirs.iter().rev() {
+            paths.extend(config_files_in(dir));
+        }
+
+        paths.extend(self.extra_paths.iter().cloned());
+        paths
+    }
+}
+
+/// `$XDG_CONFIG_HOME/ayed`, or `~/.config/ayed` when it is unset.
+fn user_config_dir() -> Option<PathBuf> {
+    let config_home = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_home.join("ayed"))
+}
+
+/// The `.ayedconf` files of `dir`, sorted by name.
+fn config_files_in(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "ayedconf"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
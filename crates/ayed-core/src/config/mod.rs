@@ -1,14 +1,24 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
 use regex::Regex;
 
 use crate::{
-    config::applied_config::{AppliedConfig, EditorConfig},
+    config::applied_config::{AppliedConfig, CommentTokens},
     input::Input,
     ui::Color,
 };
 
 mod applied_config;
+pub use applied_config::{EditorConfig, IndentRules};
+
+mod error;
+pub use error::ConfigError;
+
+mod files;
+pub use files::ConfigFiles;
 
 // TODO change hashmaps for some kind of map that keeps insertion order.
 // most likely, all hashmaps in the config modules need to be changed to
@@ -20,12 +30,22 @@ mod applied_config;
 #[derive(Default)]
 pub struct Config {
     modules: Vec<ConfigModule>,
+    /// The config files added with `add_modules_from_files`, found again on
+    /// reload.
+    files: Option<LoadedConfigFiles>,
     state: ConfigState,
     current_config: AppliedConfig,
 }
 
+struct LoadedConfigFiles {
+    files: ConfigFiles,
+    working_directory: PathBuf,
+    /// Where the modules of the files are in the list of modules.
+    module_index: usize,
+}
+
 impl Config {
-    pub fn add_module(&mut self, src: &str) -> Result<(), ()> {
+    pub fn add_module(&mut self, src: &str) -> Result<(), ConfigError> {
         let module = parse_module(src)?;
         self.modules.push(module);
         self.rebuild_current_config();
@@ -33,6 +53,87 @@ impl Config {
         Ok(())
     }
 
+    pub fn add_module_from_file(&mut self, path: &Path) -> Result<(), ConfigError> {
+        let module = parse_module_file(path)?;
+        self.modules.push(module);
+        self.rebuild_current_config();
+
+        Ok(())
+    }
+
+    /// Finds and reads the config files again, keeping the other modules and
+    /// the config state. Files that fail to load keep their previous module,
+    /// their errors are returned.
+    pub fn reload(&mut self) -> Vec<ConfigError> {
+        let Some(loaded) = &self.files else {
+            return Vec::new();
+        };
+        let module_index = loaded.module_index;
+        let paths = loaded.files.paths(&loaded.working_directory);
+
+        let (mut previous_modules, other_modules) =
+            std::mem::take(&mut self.modules)
+                .into_iter()
+                .partition::<Vec<_>, _>(|module| module.path.is_some());
+        self.modules = other_modules;
+
+        let mut file_modules = Vec::new();
+        let mut errors = Vec::new();
+        for path in paths {
+            match parse_module_file(&path) {
+                Ok(module) => file_modules.push(module),
+                Err(err) => {
+                    if let Some(previous) = previous_modules
+                        .iter()
+                        .position(|module| module.path.as_ref() == Some(&path))
+                    {
+                        file_modules.push(previous_modules.swap_remove(previous));
+                    }
+                    errors.push(err);
+                }
+            }
+        }
+        self.modules
+            .splice(module_index..module_index, file_modules);
+        self.rebuild_current_config();
+
+        errors
+    }
+
+    /// Whether `path` is one of the config files, loaded or not.
+    pub fn has_module_file(&self, path: &Path) -> bool {
+        let Some(loaded) = &self.files else {
+            return false;
+        };
+        let Ok(path) = path.canonicalize() else {
+            return false;
+        };
+        loaded
+            .files
+            .paths(&loaded.working_directory)
+            .iter()
+            .any(|file_path| file_path.canonicalize().ok().as_ref() == Some(&path))
+    }
+
+    /// Adds the modules of `files`, skipping those that fail to load. Returns
+    /// the errors of those.
+    pub fn add_modules_from_files(
+        &mut self,
+        files: &ConfigFiles,
+        working_directory: &Path,
+    ) -> Vec<ConfigError> {
+        self.files = Some(LoadedConfigFiles {
+            files: files.clone(),
+            working_directory: working_directory.to_path_buf(),
+            module_index: self.modules.len(),
+        });
+        files
+            .paths(working_directory)
+            .iter()
+            .filter_map(|path| self.add_module_from_file(path).err())
+            .collect()
+    }
+
     pub fn get(&self, key: &str) -> Option<&HashMap<String, Vec<String>>> {
         self.current_config.get(key)
     }
@@ -91,6 +192,22 @@ impl Config {
         self.current_config.theme.get(name).copied()
     }
 
+    pub fn get_brackets(&self) -> &[(char, char)] {
+        &self.current_config.brackets
+    }
+
+    pub fn get_pairs(&self) -> &[(char, char)] {
+        &self.current_config.pairs
+    }
+
+    pub fn get_comment(&self) -> &CommentTokens {
+        &self.current_config.comment
+    }
+
+    pub fn get_indent_rules(&self) -> &IndentRules {
+        &self.current_config.indent_rules
+    }
+
     pub fn get_keybind_else(&self) -> Option<&[String]> {
         let else_value = self.get("keybinds")?.get("else")?;
         Some(&else_value)
@@ -101,10 +218,11 @@ impl Config {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ConfigModule {
     // name: String,
-    // path: PathBuf,
+    /// The file the module was loaded from, if any.
+    path: Option<PathBuf>,
     mappings: Vec<ConditionalMapping>,
 }
 
@@ -174,31 +292,58 @@ impl ConfigState {
     }
 }
 
-pub fn make_builtin_config() -> Config {
+pub fn make_builtin_config() -> Result<Config, ConfigError> {
     let mut conf = Config::default();
-    conf.add_module(include_str!("./builtin.ayedconf")).unwrap();
-    conf.add_module(include_str!("./theme.ayedconf")).unwrap();
-    conf
+    conf.add_module(include_str!("./builtin.ayedconf"))?;
+    conf.add_module(include_str!("./theme.ayedconf"))?;
+    Ok(conf)
 }
 
-fn parse_module(src: &str) -> Result<ConfigModule, ()> {
+fn parse_module_file(path: &Path) -> Result<ConfigModule, ConfigError> {
+    let src = std::fs::read_to_string(path).map_err(|err| {
+        ConfigError::new(
+            Some(path.to_path_buf()),
+            format!("failed to read config: {err}"),
+        )
+    })?;
+    let mut module = parse_module(&src).map_err(|mut err| {
+        err.path = Some(path.to_path_buf());
+        err
+    })?;
+    module.path = Some(path.to_path_buf());
+    Ok(module)
+}
+
+/// The mappings whose values are regexes.
+const REGEX_MAPPINGS: [&str; 2] = ["syntax", "indent-rules"];
+
+/// The source of `template`, from its first part to its last.
+fn template_slice<'a>(src: &'a str, template: &ayed_config_parser::ast::Template) -> &'a str {
+    let (Some(first), Some(last)) = (template.parts.first(), template.parts.last()) else {
+        return &src[..0];
+    };
+    let start = ayed_config_parser::slice_span(src, first.slice).start;
+    let end = ayed_config_parser::slice_span(src, last.slice).end;
+    &src[start..end.max(start)]
+}
+
+fn parse_module(src: &str) -> Result<ConfigModule, ConfigError> {
     use ayed_config_parser::ast;
-    // TODO proper error handling
 
     let (ast, errors) = ayed_config_parser::parse_module(src);
     if !errors.is_empty() {
-        dbg!(errors);
-        return Err(());
+        return Err(ConfigError::from_parse_errors(None, src, &errors));
     }
 
     fn aux(
+        src: &str,
         mappings: &mut Vec<ConditionalMapping>,
         mixins: &mut HashMap<String, Vec<ConditionalMapping>>,
         block: &ast::Block,
         selector_stack: &[Selector],
         parent_layer: i32,
         is_top_level: bool,
-    ) {
+    ) -> Result<(), ConfigError> {
         let layer = if block.is_override { 1 } else { parent_layer };
         match &block.kind {
             ast::BlockKind::Selector(ast::SelectorBlock {
@@ -207,25 +352,35 @@ fn parse_module(src: &str) -> Result<ConfigModule, ()> {
                 children,
             }) => {
                 let mut selector_stack = selector_stack.to_vec();
-                selector_stack.push(Selector::new(state_name.slice, pattern.slice).unwrap());
+                let selector = Selector::new(state_name.slice, pattern.slice).map_err(|_| {
+                    let message = format!("invalid pattern '{}'", pattern.slice);
+                    ConfigError::at_slice(None, src, pattern.slice, message)
+                })?;
+                selector_stack.push(selector);
 
                 for child in children {
-                    aux(mappings, mixins, child, &selector_stack, layer, false);
+                    aux(src, mappings, mixins, child, &selector_stack, layer, false)?;
                 }
             }
             ast::BlockKind::Mapping(ast::MappingBlock { name, entries }) => {
                 let mut mapping: HashMap<String, Vec<String>> = Default::default();
                 for entry in entries {
-                    mapping
+                    let values = mapping
                         .entry(entry.name.to_string()) // FIXME unecessary allocation
-                        .or_default()
-                        .extend(entry.values.iter().map(|template| {
-                            let mut buf = String::new();
-                            for part in &template.parts {
-                                buf.push_str(part.slice);
-                            }
-                            buf
-                        }));
+                        .or_default();
+                    for template in &entry.values {
+                        let mut buf = String::new();
+                        for part in &template.parts {
+                            buf.push_str(part.slice);
+                        }
+                        // Invalid regexes are reported here rather than when applying them.
+                        if REGEX_MAPPINGS.contains(&name.slice) && Regex::new(&buf).is_err() {
+                            let message = format!("invalid regex '{buf}'");
+                            let slice = template_slice(src, template);
+                            return Err(ConfigError::at_slice(None, src, slice, message));
+                        }
+                        values.push(buf);
+                    }
                 }
                 mappings.push(ConditionalMapping {
                     name: name.to_string(),
@@ -236,45 +391,92 @@ fn parse_module(src: &str) -> Result<ConfigModule, ()> {
             }
             ast::BlockKind::Mixin(ast::MixinBlock { name, children }) => {
                 if !is_top_level {
-                    unimplemented!("non top level mixins not supported yet");
+                    let message = "mixins must be declared at the top level";
+                    return Err(ConfigError::at_slice(None, src, name.slice, message));
                 }
 
                 let mut mixin_mappings = Vec::new();
                 for child in children {
                     aux(
+                        src,
                         &mut mixin_mappings,
                         mixins,
                         child,
                         &selector_stack,
                         layer,
                         false,
-                    );
+                    )?;
                 }
                 mixins.insert(name.to_string(), mixin_mappings);
             }
             ast::BlockKind::Use(mixin_name) => {
-                mappings.extend(mixins.get(mixin_name.slice).unwrap().iter().cloned().map(
-                    |mut mapping| {
-                        mapping.layer += parent_layer;
-                        mapping.selectors.extend(selector_stack.iter().cloned());
-                        mapping
-                    },
-                ));
+                let mixin = mixins.get(mixin_name.slice).ok_or_else(|| {
+                    let message = format!("unknown mixin '{}'", mixin_name.slice);
+                    ConfigError::at_slice(None, src, mixin_name.slice, message)
+                })?;
+                mappings.extend(mixin.iter().cloned().map(|mut mapping| {
+                    mapping.layer += parent_layer;
+                    mapping.selectors.extend(selector_stack.iter().cloned());
+                    mapping
+                }));
             }
         }
+        Ok(())
     }
 
     let mut mappings = Vec::new();
     let mut mixins = HashMap::default();
     for block in &ast.top_level_blocks {
         aux(
+            src,
             &mut mappings,
             &mut mixins,
             block,
             &[],
             Default::default(),
             true,
-        );
+        )?;
+    }
+    Ok(ConfigModule {
+        path: None,
+        mappings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reload_keeps_modules_of_failing_files() {
+        let dir = std::env::temp_dir().join(format!("ayed-config-reload-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (a, b) = (dir.join("a.ayedconf"), dir.join("b.ayedconf"));
+        std::fs::write(&a, "test {\n    a 1\n    shared a\n}\n").unwrap();
+        std::fs::write(&b, "test {\n    b 1\n    shared b\n}\n").unwrap();
+
+        let mut config = Config::default();
+        let files = ConfigFiles {
+            no_user_config: true,
+            extra_paths: vec![a.clone(), b.clone()],
+        };
+        assert!(config.add_modules_from_files(&files, &dir).is_empty());
+
+        std::fs::write(&a, "test {\n    a 2\n    shared a\n}\n").unwrap();
+        std::fs::write(&b, "test {\n    b 2\n").unwrap();
+        let errors = config.reload();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path.as_ref(), Some(&b));
+        assert_eq!(config.get_entry_value("test", "a"), Ok("2"));
+        assert_eq!(config.get_entry_value("test", "b"), Ok("1"));
+        // The previous module keeps its precedence.
+        assert_eq!(config.get_entry_value("test", "shared"), Ok("b"));
+
+        std::fs::write(&b, "test {\n    b 3\n}\n").unwrap();
+        assert!(config.reload().is_empty());
+        assert_eq!(config.get_entry_value("test", "b"), Ok("3"));
+        assert_eq!(config.get_entry_value("test", "shared"), Ok("a"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
     }
-    Ok(ConfigModule { mappings })
 }
@@ -1,12 +1,15 @@
 use crate::{
     command::{CommandQueue, CommandRegistry, ExecuteCommandContext, parse_command},
-    commands, config,
+    commands,
+    config::{self, ConfigError},
     input::Input,
     panels::{self, Modeline, Panels, RenderPanelContext},
     state::{Resources, State},
     ui::{Rect, Size, ui_state::UiState},
 };
 
+pub use crate::config::ConfigFiles;
+
 #[derive(Default)]
 pub struct Core {
     pub commands: CommandRegistry,
@@ -19,22 +22,46 @@ pub struct Core {
 
 impl Core {
     pub fn with_builtins() -> Self {
+        Self::with_builtins_and_config_files(&ConfigFiles::default())
+    }
+
+    /// Like `with_builtins`, also loading the configuration `files`. Errors
+    /// loading them are shown in the modeline and by `config-errors`.
+    pub fn with_builtins_and_config_files(files: &ConfigFiles) -> Self {
         let mut this = Self::default();
 
         this.register_builtin_events();
 
         commands::register_builtin_commands(&mut this.commands);
 
-        this.state.config = config::make_builtin_config();
+        this.state.working_directory = std::env::current_dir().unwrap();
+
+        let mut config_errors = Vec::new();
+        this.state.config = match config::make_builtin_config() {
+            Ok(config) => config,
+            Err(err) => {
+                config_errors.push(err);
+                Default::default()
+            }
+        };
+        config_errors.extend(
+            this.state
+                .config
+                .add_modules_from_files(files, &this.state.working_directory),
+        );
 
         panels::warpdrive::commands::register_warpdrive_commands(&mut this.commands);
         panels::file_picker::commands::register_file_picker_commands(&mut this.commands);
-
-        this.state.working_directory = std::env::current_dir().unwrap();
+        panels::marks_picker::commands::register_marks_picker_commands(&mut this.commands);
 
         this.queue_command("started".to_string());
         this.tick();
 
+        if let Some(err_msg) = ConfigError::summary(&config_errors) {
+            this.state.modeline.set_error(err_msg, &this.state.config);
+            this.state.config_errors = config_errors;
+        }
+
         this
     }
 
@@ -150,6 +177,7 @@ impl Core {
 
         panels.extend(self.panels.hover_info.render(&render_ctx));
         panels.extend(self.panels.file_picker.render(&render_ctx));
+        panels.extend(self.panels.marks_picker.render(&render_ctx));
 
         if let Some(panel) = self.panels.warpdrive.render(&render_ctx) {
             panels.push(panel);
@@ -175,6 +203,8 @@ impl Core {
         self.commands.register_event("time-tick");
         self.commands.register_event("buffer-opened");
         self.commands.register_event("buffer-modified");
+        self.commands.register_event("buffer-before-write");
+        self.commands.register_event("buffer-written");
         self.commands.register_event("buffer-closed");
         self.commands.register_event("selections-modified");
     }
@@ -252,6 +282,9 @@ impl Core {
             .grown(-1, -(Modeline::HEIGHT as i32 + 1), -4, -4),
         );
         self.state.file_picker_rect = self.panels.file_picker.rect();
+        self.panels
+            .marks_picker
+            .set_rect(self.panels.file_picker.rect());
 
         self.panels.hover_info.set_rect(
             Rect::new(
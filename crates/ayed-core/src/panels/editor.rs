@@ -1,7 +1,7 @@
 use crate::{
     position::{Column, Position, Row},
     slotmap::Handle,
-    state::View,
+    state::{View, find_bracket_pair, search_matches_in_rows},
     ui::{
         Rect, Style,
         ui_state::{StyledRegion, UiPanel},
@@ -74,6 +74,9 @@ impl Editor {
         let clr_selection = clr("selection");
         let clr_selection_extra = clr("selection-extra").or(clr_selection);
 
+        let clr_bracket_match = clr("bracket-match");
+        let clr_search_match = clr("search-match");
+
         for i in 0..line_count {
             spans.push(StyledRegion {
                 from: Position::new(0, i),
@@ -185,6 +188,63 @@ impl Editor {
             }
         }
 
+        // Matching brackets
+        let highlights = ctx.state.highlights.get(&view.buffer).map(Vec::as_slice);
+        let brackets = ctx.state.config.get_brackets();
+        for selection in selections.iter() {
+            let Some((bracket, matching)) =
+                find_bracket_pair(buffer, selection.cursor, brackets, highlights)
+            else {
+                continue;
+            };
+            for pos in [bracket, matching] {
+                let pos = view.map_logical_position_to_view_position(
+                    buffer.map_true_position_to_logical_position(pos, &ctx.state.config),
+                );
+                spans.push(StyledRegion {
+                    from: pos,
+                    to: pos,
+                    style: Style {
+                        background_color: clr_bracket_match,
+                        bold: true,
+                        ..Default::default()
+                    },
+                    priority: 253,
+                });
+            }
+        }
+
+        // Search matches
+        if let Some(regex) = &ctx.state.search.regex
+            && ctx.state.active_editor_view == Some(view_handle)
+        {
+            let first_row = view.top_left.row;
+            let last_row = first_row + size.row - 1;
+            for (row, from, to) in search_matches_in_rows(buffer, regex, first_row, last_row) {
+                let from = view.map_logical_position_to_view_position(
+                    buffer.map_true_position_to_logical_position(
+                        Position::new(from, row),
+                        &ctx.state.config,
+                    ),
+                );
+                let to = view.map_logical_position_to_view_position(
+                    buffer.map_true_position_to_logical_position(
+                        Position::new(to, row),
+                        &ctx.state.config,
+                    ),
+                );
+                spans.push(StyledRegion {
+                    from,
+                    to,
+                    style: Style {
+                        background_color: clr_search_match,
+                        ..Default::default()
+                    },
+                    priority: 252,
+                });
+            }
+        }
+
         // FIXME Same as above, doesnt support highlights that span multiple fragments
         // Syntax highlight
         if let Some(highlights) = ctx.state.highlights.get(&view.buffer) {
@@ -205,7 +265,7 @@ impl Editor {
             content,
             spans,
         };
-        self.render_idents(&mut editor_panel, view_line_start, ctx);
+        self.render_idents(&mut editor_panel, view_line_start, view_handle, ctx);
         vec![editor_panel]
     }
 
@@ -213,11 +273,16 @@ impl Editor {
         &self,
         editor_panel: &mut UiPanel,
         view_line_start: usize,
+        view_handle: Handle<View>,
         ctx: &RenderPanelContext,
     ) {
         let foreground_color = ctx.state.config.get_theme_color("editor-indent");
         let background_color = ctx.state.config.get_theme_color("editor-bg");
-        let indent_size = ctx.state.config.get_editor().indent_size;
+        let buffer = ctx
+            .resources
+            .buffers
+            .get(ctx.resources.views.get(view_handle).buffer);
+        let indent_size = buffer.editor_config(&ctx.state.config).indent_size.max(1);
 
         // NOTE: editor's view lines are logical lines, so all indent should be normalized to spaces already, and so we can assume 1 byte == 1 char.
 
@@ -1,15 +1,8 @@
 use std::{collections::BTreeMap, path::{Path, PathBuf}};
 
 use crate::{
-    position::Position,
-    ui::{
-        Color, Rect, Style,
-        ui_state::{StyledRegion, UiPanel},
-    },
-    utils::{
-        render_utils::{BORDER_ALL, decorated_rectangle, separator_h},
-        string_utils::line_clamped_filled,
-    },
+    ui::{Color, Rect, Style, ui_state::UiPanel},
+    utils::render_utils::{BORDER_ALL, decorated_rectangle, list_panel, separator_h},
 };
 
 use super::{Editor, FocusedPanel, RenderPanelContext};
@@ -72,49 +65,30 @@ fn render_file_list(
     default_style: Style,
     text_color: Option<Color>,
 ) -> UiPanel {
-    let size = rect.size();
-    let mut content = Vec::new();
-    let mut spans = Vec::new();
-
-    let file_list_is_empty = ctx.state.file_picker.list_items.is_empty();
-
-    for y in 0..size.row {
-        let mut style = default_style;
-        if !file_list_is_empty && y as usize == ctx.state.file_picker.selected_item {
-            style.invert = true;
-        }
-        let text = if file_list_is_empty && y == 0 {
-            style.foreground_color = Some(Color::rgb(112, 112, 112));
-            "no such file"
-        } else if let Some(item) = ctx.state.file_picker.list_items.get(y as usize) {
-            if matches!(item, FileListItem::Section { .. }) {
-                style.foreground_color = Some(Color::rgb(112, 112, 112));
-                style.bold = true;
-            } else {
-                style.foreground_color = text_color;
-            }
-
-            item.text()
-        } else {
-            ""
-        };
-        let line = line_clamped_filled(text, 0, size.column as usize, ' ');
-        content.push(line);
-
-        spans.push(StyledRegion {
-            from: Position::new(0, y as i32),
-            to: Position::new(size.column as i32, y as i32),
-            style,
-            priority: 2,
-        });
+    let file_picker = &ctx.state.file_picker;
+    let dimmed_style = Style {
+        foreground_color: Some(Color::rgb(112, 112, 112)),
+        ..default_style
+    };
+    if file_picker.list_items.is_empty() {
+        return list_panel(rect, default_style, [("no such file", dimmed_style)]);
     }
 
-    UiPanel {
-        position: rect.top_left(),
-        size,
-        content,
-        spans,
-    }
+    let rows = file_picker.list_items.iter().enumerate().map(|(i, item)| {
+        let mut style = match item {
+            FileListItem::Section { .. } => Style {
+                bold: true,
+                ..dimmed_style
+            },
+            FileListItem::File { .. } => Style {
+                foreground_color: text_color,
+                ..default_style
+            },
+        };
+        style.invert = i == file_picker.selected_item;
+        (item.text(), style)
+    });
+    list_panel(rect, default_style, rows)
 }
 
 pub enum FileListItem {
@@ -0,0 +1,176 @@
+use crate::{
+    state::{Resources, State},
+    ui::{Color, Rect, Style, ui_state::UiPanel},
+    utils::render_utils::{BORDER_ALL, decorated_rectangle, list_panel},
+};
+
+use super::{FocusedPanel, RenderPanelContext};
+
+#[derive(Default)]
+pub struct MarksPicker {
+    rect: Rect,
+}
+
+impl MarksPicker {
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    pub fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    pub fn render(&self, ctx: &RenderPanelContext) -> Vec<UiPanel> {
+        if ctx.state.focused_panel != FocusedPanel::MarksPicker {
+            return Vec::new();
+        }
+
+        let default_style = Style {
+            background_color: ctx.state.config.get_theme_color("box-bg"),
+            foreground_color: ctx.state.config.get_theme_color("box-fg"),
+            ..Default::default()
+        };
+        let back_panel = decorated_rectangle(
+            self.rect.top_left(),
+            self.rect.size(),
+            default_style,
+            BORDER_ALL,
+        );
+
+        let list_rect = Rect::from_positions(self.rect.top_left(), self.rect.bottom_right())
+            .grown(-1, -1, -2, -2);
+        let text_color = ctx.state.config.get_theme_color("editor-fg");
+        let list_panel = render_mark_list(ctx.state, list_rect, default_style, text_color);
+
+        vec![back_panel, list_panel]
+    }
+}
+
+fn render_mark_list(
+    state: &State,
+    rect: Rect,
+    default_style: Style,
+    text_color: Option<Color>,
+) -> UiPanel {
+    let marks_picker = &state.marks_picker;
+    if marks_picker.list_items.is_empty() {
+        let dimmed_style = Style {
+            foreground_color: Some(Color::rgb(112, 112, 112)),
+            ..default_style
+        };
+        return list_panel(rect, default_style, [("no marks", dimmed_style)]);
+    }
+
+    let rows = marks_picker.list_items.iter().enumerate().map(|(i, item)| {
+        let style = Style {
+            foreground_color: text_color,
+            invert: i == marks_picker.selected_item,
+            ..default_style
+        };
+        (item.text.as_str(), style)
+    });
+    list_panel(rect, default_style, rows)
+}
+
+pub struct MarkListItem {
+    pub name: String,
+    text: String,
+}
+
+/// Lists the marks, sorted by name.
+pub fn mark_list_items(state: &State, resources: &Resources) -> Vec<MarkListItem> {
+    let mut items = state
+        .marks
+        .keys()
+        .filter_map(|name| Some((name, state.mark(name, resources)?)))
+        .map(|(name, mark)| {
+            let path = mark
+                .path
+                .as_deref()
+                .map(|path| state.denormalize_path(path).to_string_lossy().to_string())
+                .unwrap_or_else(|| "<scratch>".to_string());
+            let count = mark.selections.count();
+            let cursor = mark.selections.primary().cursor;
+            let plural = if count == 1 { "" } else { "s" };
+            MarkListItem {
+                name: name.clone(),
+                text: format!("{name}  {path} {cursor} ({count} selection{plural})"),
+            }
+        })
+        .collect::<Vec<_>>();
+    items.sort_by(|a, b| a.name.cmp(&b.name));
+    items
+}
+
+#[derive(Default)]
+pub struct MarksPickerState {
+    pub list_items: Vec<MarkListItem>,
+    pub selected_item: usize,
+}
+
+impl MarksPickerState {
+    pub fn set_list_items(&mut self, list_items: Vec<MarkListItem>) {
+        self.list_items = list_items;
+        self.selected_item = 0;
+    }
+
+    pub fn select_next(&mut self) {
+        self.select_impl(1);
+    }
+
+    pub fn select_previous(&mut self) {
+        self.select_impl(-1);
+    }
+
+    pub fn selected(&self) -> Option<&MarkListItem> {
+        self.list_items.get(self.selected_item)
+    }
+
+    fn select_impl(&mut self, direction: i32) {
+        if self.list_items.is_empty() {
+            return;
+        }
+        let i = self.selected_item as i32 + direction.signum();
+        self.selected_item = i32::rem_euclid(i, self.list_items.len() as i32) as usize;
+    }
+}
+
+pub mod commands {
+    use crate::{
+        command::{CommandRegistry, options::Options},
+        panels::marks_picker::mark_list_items,
+    };
+
+    pub fn register_marks_picker_commands(cr: &mut CommandRegistry) {
+        cr.register("marks-picker-confirm", |_opt, ctx| {
+            let Some(item) = ctx.state.marks_picker.selected() else {
+                return Ok(());
+            };
+
+            let name = item.name.clone();
+            ctx.queue.push("panel-focus editor");
+            ctx.queue.push(format!("mark-restore {name}"));
+
+            Ok(())
+        });
+
+        cr.register("marks-picker-select", |opt, ctx| {
+            let opts = Options::new().flag("next").flag("previous").parse(opt)?;
+
+            if opts.contains("next") {
+                ctx.state.marks_picker.select_next();
+            }
+            if opts.contains("previous") {
+                ctx.state.marks_picker.select_previous();
+            }
+
+            Ok(())
+        });
+
+        cr.register("marks-picker-fill-list", |_opt, ctx| {
+            let list_items = mark_list_items(ctx.state, ctx.resources);
+            ctx.state.marks_picker.set_list_items(list_items);
+            Ok(())
+        });
+    }
+}
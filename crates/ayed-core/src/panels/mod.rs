@@ -13,6 +13,9 @@ pub use self::modeline::Modeline;
 pub mod file_picker;
 pub use self::file_picker::FilePicker;
 
+pub mod marks_picker;
+pub use self::marks_picker::MarksPicker;
+
 pub mod hover_info;
 pub use self::hover_info::HoverInfo;
 
@@ -31,6 +34,7 @@ pub struct Panels {
     pub line_numbers: LineNumbers,
     pub modeline: Modeline,
     pub file_picker: FilePicker,
+    pub marks_picker: MarksPicker,
     pub hover_info: HoverInfo,
     pub warpdrive: Warpdrive,
     pub combo: Combo,
@@ -43,6 +47,7 @@ pub enum FocusedPanel {
     Editor,
     Modeline(Handle<View>),
     FilePicker(Handle<View>),
+    MarksPicker,
     Warpdrive,
 }
 
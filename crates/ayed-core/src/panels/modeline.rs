@@ -44,8 +44,13 @@ impl Modeline {
 
             let mut editor_panel = editor.render(ctx).remove(0);
 
+            let prompt = match ctx.state.config.state_value("panel") {
+                Some("search") => '/',
+                Some("goto") => '#',
+                _ => '›',
+            };
             for line in &mut editor_panel.content {
-                line.insert(0, '›');
+                line.insert(0, prompt);
             }
 
             for region in &mut editor_panel.spans {
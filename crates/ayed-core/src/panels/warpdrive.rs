@@ -235,6 +235,7 @@ pub mod commands {
                         Selection::new().with_anchor(start).with_cursor(end),
                         &[],
                     );
+                    ctx.queue.push("jump-record");
                     ctx.queue.push(format!("selections-set {selections}"));
                     ctx.queue.push("panel-focus editor");
                 }
@@ -115,6 +115,12 @@ impl<V, K> SlotMap<V, K> {
         maybe_value.unwrap()
     }
 
+    pub fn contains(&self, k: Handle<K>) -> bool {
+        self.slots
+            .get(k.id as usize)
+            .is_some_and(|slot| slot.generation == k.generation && slot.element.is_some())
+    }
+
     pub fn get(&self, k: Handle<K>) -> &V {
         self.slots
             .get(k.id as usize)
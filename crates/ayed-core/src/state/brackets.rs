@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+
+use crate::position::{Column, Position, Row};
+
+use super::{Highlight, TextBuffer};
+
+/// Syntax rules whose highlighted regions are ignored when matching brackets.
+const SKIPPED_SYNTAX_RULES: &[&str] = &["string", "comment"];
+
+/// Finds the bracket on `position`, or if there is none, the bracket right
+/// before it. Returns the position of that bracket and the position of its
+/// matching bracket.
+/// When highlights are provided, brackets inside strings and comments are
+/// skipped, unless the bracket at `position` is itself inside one.
+pub fn find_bracket_pair(
+    buffer: &TextBuffer,
+    position: Position,
+    brackets: &[(char, char)],
+    highlights: Option<&[Highlight]>,
+) -> Option<(Position, Position)> {
+    let skipped_regions = SkippedRegions::new(highlights.unwrap_or_default());
+
+    let candidates = [position, position.offset((-1, 0))];
+    for candidate in candidates {
+        let Some(ch) = char_at(buffer, candidate) else {
+            continue;
+        };
+        let Some(&(open, close)) = brackets.iter().find(|(o, c)| *o == ch || *c == ch) else {
+            continue;
+        };
+
+        let skip =
+            |pos: Position| !skipped_regions.contains(candidate) && skipped_regions.contains(pos);
+        let matching = if ch == open {
+            find_close_bracket(buffer, candidate, open, close, skip)
+        } else {
+            find_open_bracket(buffer, candidate, open, close, skip)
+        };
+        return matching.map(|matching| (candidate, matching));
+    }
+
+    None
+}
+
+/// Finds the nearest `open` and `close` pair enclosing the region from `start`
+/// to `end`. Brackets on `start` or `end` themselves are considered enclosing.
+/// When `open` and `close` are the same character (ex: quotes), the pair must
+/// be on the lines of `start` and `end`, since nesting can't be told apart.
+pub fn find_enclosing_bracket_pair(
+    buffer: &TextBuffer,
+    start: Position,
+    end: Position,
+    open: char,
+    close: char,
+) -> Option<(Position, Position)> {
+    if open == close {
+        let start_line = buffer.line(start.row)?.chars().collect::<Vec<_>>();
+        let end_line = buffer.line(end.row)?.chars().collect::<Vec<_>>();
+        // A quote on `start` may be either the opening or the closing one, so
+        // try both.
+        for open_search_start in [start.column, start.column - 1] {
+            let Some(open_column) = (0..=open_search_start)
+                .rev()
+                .find(|&column| start_line.get(column as usize) == Some(&open))
+            else {
+                continue;
+            };
+            let open_pos = Position::new(open_column, start.row);
+            let close_search_start = if end.row == start.row {
+                end.column.max(open_column + 1)
+            } else {
+                end.column
+            };
+            let Some(close_column) = (close_search_start..end_line.len() as Column)
+                .find(|&column| end_line.get(column as usize) == Some(&close))
+            else {
+                continue;
+            };
+            return Some((open_pos, Position::new(close_column, end.row)));
+        }
+        return None;
+    }
+
+    let mut depth = 0;
+    for row in (0..=start.row).rev() {
+        let line = buffer.line(row)?;
+        let chars = line.chars().collect::<Vec<_>>();
+        let end_column = if row == start.row {
+            start.column as usize + 1
+        } else {
+            chars.len()
+        };
+        for column in (0..end_column.min(chars.len())).rev() {
+            let ch = chars[column];
+            let pos = Position::new(column as Column, row);
+            if ch == close && pos != start {
+                depth += 1;
+            } else if ch == open {
+                if depth > 0 {
+                    depth -= 1;
+                    continue;
+                }
+                // This open bracket isn't closed by anything before it, but
+                // it only encloses the region if its match comes after it.
+                let close_pos = find_close_bracket(buffer, pos, open, close, |_| false);
+                if let Some(close_pos) = close_pos
+                    && close_pos >= end
+                {
+                    return Some((pos, close_pos));
+                }
+            }
+        }
+    }
+    None
+}
+
+fn find_close_bracket(
+    buffer: &TextBuffer,
+    from: Position,
+    open: char,
+    close: char,
+    skip: impl Fn(Position) -> bool,
+) -> Option<Position> {
+    let mut depth = 0;
+    for row in from.row..buffer.line_count() {
+        let line = buffer.line(row)?;
+        let start_column = if row == from.row { from.column } else { 0 };
+        for (column, ch) in line.chars().enumerate().skip(start_column as usize) {
+            let pos = Position::new(column as Column, row);
+            if (ch != open && ch != close) || skip(pos) {
+                continue;
+            }
+            if ch == open {
+                depth += 1;
+            } else {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(pos);
+                }
+            }
+        }
+    }
+    None
+}
+
+fn find_open_bracket(
+    buffer: &TextBuffer,
+    from: Position,
+    open: char,
+    close: char,
+    skip: impl Fn(Position) -> bool,
+) -> Option<Position> {
+    let mut depth = 0;
+    for row in (0..=from.row).rev() {
+        let line = buffer.line(row)?;
+        let chars = line.chars().collect::<Vec<_>>();
+        let end_column = if row == from.row {
+            from.column as usize + 1
+        } else {
+            chars.len()
+        };
+        for column in (0..end_column.min(chars.len())).rev() {
+            let ch = chars[column];
+            let pos = Position::new(column as Column, row);
+            if (ch != open && ch != close) || skip(pos) {
+                continue;
+            }
+            if ch == close {
+                depth += 1;
+            } else {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(pos);
+                }
+            }
+        }
+    }
+    None
+}
+
+fn char_at(buffer: &TextBuffer, position: Position) -> Option<char> {
+    if position.column < 0 {
+        return None;
+    }
+    buffer
+        .line(position.row)?
+        .chars()
+        .nth(position.column as usize)
+}
+
+struct SkippedRegions {
+    regions_per_row: HashMap<Row, Vec<(Column, Column)>>,
+}
+
+impl SkippedRegions {
+    fn new(highlights: &[Highlight]) -> Self {
+        let mut regions_per_row: HashMap<Row, Vec<(Column, Column)>> = HashMap::new();
+        for hl in highlights {
+            if !SKIPPED_SYNTAX_RULES.contains(&hl.rule.as_str()) {
+                continue;
+            }
+            let region = &hl.styled_region;
+            regions_per_row
+                .entry(region.from.row)
+                .or_default()
+                .push((region.from.column, region.to.column));
+        }
+        Self { regions_per_row }
+    }
+
+    fn contains(&self, position: Position) -> bool {
+        self.regions_per_row
+            .get(&position.row)
+            .is_some_and(|regions| {
+                regions
+                    .iter()
+                    .any(|&(from, to)| from <= position.column && position.column <= to)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::ui_state::StyledRegion;
+
+    use super::*;
+
+    const BRACKETS: &[(char, char)] = &[('(', ')'), ('[', ']'), ('{', '}')];
+
+    fn buffer(content: &str) -> TextBuffer {
+        let mut buffer = TextBuffer::new_empty();
+        buffer.lines = content.split('\n').map(str::to_string).collect();
+        buffer
+    }
+
+    #[test]
+    fn find_bracket_pair_across_lines() {
+        let buffer = buffer("fn a() {\n    (b[0]);\n}");
+        let pair = find_bracket_pair(&buffer, Position::new(7, 0), BRACKETS, None);
+        assert_eq!(pair, Some((Position::new(7, 0), Position::new(0, 2))));
+        let pair = find_bracket_pair(&buffer, Position::new(0, 2), BRACKETS, None);
+        assert_eq!(pair, Some((Position::new(0, 2), Position::new(7, 0))));
+    }
+
+    #[test]
+    fn find_bracket_pair_next_to_cursor() {
+        let buffer = buffer("(a)  ");
+        let pair = find_bracket_pair(&buffer, Position::new(3, 0), BRACKETS, None);
+        assert_eq!(pair, Some((Position::new(2, 0), Position::new(0, 0))));
+        let pair = find_bracket_pair(&buffer, Position::new(4, 0), BRACKETS, None);
+        assert_eq!(pair, None);
+    }
+
+    #[test]
+    fn find_bracket_pair_skips_strings() {
+        let buffer = buffer("(\")\")");
+        let highlights = [Highlight {
+            styled_region: StyledRegion {
+                from: Position::new(1, 0),
+                to: Position::new(3, 0),
+                ..Default::default()
+            },
+            rule: "string".to_string(),
+        }];
+        let pair = find_bracket_pair(&buffer, Position::ZERO, BRACKETS, Some(&highlights));
+        assert_eq!(pair, Some((Position::ZERO, Position::new(4, 0))));
+        let pair = find_bracket_pair(&buffer, Position::ZERO, BRACKETS, None);
+        assert_eq!(pair, Some((Position::ZERO, Position::new(2, 0))));
+    }
+
+    #[test]
+    fn find_enclosing_bracket_pair_skips_nested_pairs() {
+        let buffer = buffer(
+            "(a (b) [c
+] d)",
+        );
+        let pair = find_enclosing_bracket_pair(
+            &buffer,
+            Position::new(1, 0),
+            Position::new(1, 1),
+            '(',
+            ')',
+        );
+        assert_eq!(pair, Some((Position::ZERO, Position::new(3, 1))));
+        let pair = find_enclosing_bracket_pair(
+            &buffer,
+            Position::new(5, 0),
+            Position::new(5, 0),
+            '(',
+            ')',
+        );
+        assert_eq!(pair, Some((Position::new(3, 0), Position::new(5, 0))));
+    }
+}
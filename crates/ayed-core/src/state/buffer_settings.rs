@@ -0,0 +1,199 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::{config::EditorConfig, utils::editorconfig::editorconfig_properties};
+
+use super::DetectedIndentation;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+    Cr,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Charset {
+    Utf8,
+    Utf8Bom,
+}
+
+/// Settings of a single buffer, like those of its `.editorconfig` files.
+/// Those that are set override the editor config.
+#[derive(Debug, Default, Clone)]
+pub struct BufferSettings {
+    pub indent_with_tabs: Option<bool>,
+    pub indent_size: Option<i32>,
+    pub tab_width: Option<i32>,
+    /// The line ending that lines are written with. When unset, lines are
+    /// written with `\n`, carriage returns being part of their content.
+    pub line_ending: Option<LineEnding>,
+    /// When unset, a byte order mark is written only if the buffer has one.
+    pub charset: Option<Charset>,
+    pub trim_trailing_whitespace: Option<bool>,
+    pub insert_final_newline: Option<bool>,
+    pub text_width: Option<u32>,
+    /// The indentation detected from the content of the buffer, which
+    /// applies when the editor config enables detection and neither it nor
+    /// another setting of the buffer sets the indentation.
+    pub detected_indentation: Option<DetectedIndentation>,
+}
+
+impl BufferSettings {
+    /// The settings from the `.editorconfig` files that apply to `path`.
+    /// Unknown properties and values are ignored.
+    pub fn from_editorconfig(path: &Path) -> Self {
+        Self::from_editorconfig_properties(&editorconfig_properties(path))
+    }
+
+    fn from_editorconfig_properties(properties: &HashMap<String, String>) -> Self {
+        let get = |name: &str| properties.get(name).map(String::as_str);
+        let get_bool = |name: &str| get(name)?.parse::<bool>().ok();
+        let get_width = |name: &str| get(name)?.parse::<i32>().ok().filter(|&w| w > 0);
+
+        let tab_width = get_width("tab_width");
+        // `indent_size = tab` indents by the tab width.
+        let indent_size = match get("indent_size") {
+            Some("tab") => tab_width,
+            _ => get_width("indent_size"),
+        };
+
+        Self {
+            indent_with_tabs: match get("indent_style") {
+                Some("tab") => Some(true),
+                Some("space") => Some(false),
+                _ => None,
+            },
+            indent_size,
+            tab_width,
+            line_ending: match get("end_of_line") {
+                Some("lf") => Some(LineEnding::Lf),
+                Some("crlf") => Some(LineEnding::CrLf),
+                Some("cr") => Some(LineEnding::Cr),
+                _ => None,
+            },
+            // Other charsets aren't supported.
+            charset: match get("charset") {
+                Some("utf-8") => Some(Charset::Utf8),
+                Some("utf-8-bom") => Some(Charset::Utf8Bom),
+                _ => None,
+            },
+            trim_trailing_whitespace: get_bool("trim_trailing_whitespace"),
+            insert_final_newline: get_bool("insert_final_newline"),
+            text_width: get_width("max_line_length").map(|w| w as u32),
+            detected_indentation: None,
+        }
+    }
+
+    /// `editor` with the settings that are set applied over it.
+    pub fn apply(&self, editor: &EditorConfig) -> EditorConfig {
+        let mut editor = editor.clone();
+        if editor.detect_indentation
+            && let Some(detected) = self.detected_indentation
+        {
+            if !editor.indent_style_is_set {
+                editor.indent_with_tabs = detected.with_tabs;
+            }
+            if !editor.indent_size_is_set
+                && let Some(size) = detected.size
+            {
+                editor.indent_size = size;
+            }
+        }
+        if let Some(indent_with_tabs) = self.indent_with_tabs {
+            editor.indent_with_tabs = indent_with_tabs;
+        }
+        if let Some(indent_size) = self.indent_size {
+            editor.indent_size = indent_size;
+        }
+        if let Some(tab_width) = self.tab_width {
+            editor.tab_width = Some(tab_width);
+        }
+        if let Some(trim_trailing_whitespace) = self.trim_trailing_whitespace {
+            editor.trim_trailing_whitespace = trim_trailing_whitespace;
+        }
+        if let Some(insert_final_newline) = self.insert_final_newline {
+            editor.insert_final_newline = insert_final_newline;
+        }
+        if let Some(text_width) = self.text_width {
+            editor.text_width = text_width;
+        }
+        editor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn properties(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn reads_editorconfig_properties() {
+        let settings = BufferSettings::from_editorconfig_properties(&properties(&[
+            ("indent_style", "tab"),
+            ("indent_size", "tab"),
+            ("tab_width", "8"),
+            ("end_of_line", "crlf"),
+            ("max_line_length", "100"),
+        ]));
+        assert_eq!(settings.indent_with_tabs, Some(true));
+        assert_eq!(settings.indent_size, Some(8));
+        assert_eq!(settings.tab_width, Some(8));
+        assert_eq!(settings.line_ending, Some(LineEnding::CrLf));
+        assert_eq!(settings.text_width, Some(100));
+    }
+
+    #[test]
+    fn ignores_unset_and_unknown_values() {
+        let settings = BufferSettings::from_editorconfig_properties(&properties(&[
+            ("indent_style", "unset"),
+            ("indent_size", "unset"),
+            ("tab_width", "0"),
+            ("charset", "latin1"),
+            ("trim_trailing_whitespace", "unset"),
+        ]));
+        assert_eq!(settings.indent_with_tabs, None);
+        assert_eq!(settings.indent_size, None);
+        assert_eq!(settings.tab_width, None);
+        assert_eq!(settings.charset, None);
+        assert_eq!(settings.trim_trailing_whitespace, None);
+
+        // Without a tab width, `indent_size = tab` leaves the size to the editor.
+        let settings =
+            BufferSettings::from_editorconfig_properties(&properties(&[("indent_size", "tab")]));
+        assert_eq!(settings.indent_size, None);
+    }
+
+    #[test]
+    fn detected_indentation_yields_to_the_config() {
+        let settings = BufferSettings {
+            detected_indentation: Some(DetectedIndentation {
+                with_tabs: true,
+                size: None,
+            }),
+            ..Default::default()
+        };
+        assert!(settings.apply(&EditorConfig::default()).indent_with_tabs);
+
+        let editor = EditorConfig {
+            indent_style_is_set: true,
+            ..Default::default()
+        };
+        assert!(!settings.apply(&editor).indent_with_tabs);
+    }
+}
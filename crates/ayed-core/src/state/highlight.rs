@@ -17,6 +17,8 @@ use super::TextBuffer;
 pub struct Highlight {
     // Styled region but the position is content relative, not panel relative.
     pub styled_region: StyledRegion,
+    /// Name of the syntax rule that produced this highlight (ex: "string").
+    pub rule: String,
 }
 
 pub fn regex_syntax_highlight(
@@ -26,7 +28,7 @@ pub fn regex_syntax_highlight(
 ) -> Vec<Highlight> {
     let mut highlights = Vec::new();
 
-    let mut rules: Vec<(Vec<Regex>, Color, Option<u8>)> = Vec::new();
+    let mut rules: Vec<(&str, Vec<Regex>, Color, Option<u8>)> = Vec::new();
     for (rule_name, regexes) in syntax {
         let Some(values) = syntax_style.get(rule_name) else {
             continue;
@@ -42,14 +44,14 @@ pub fn regex_syntax_highlight(
         }
         let Some(color) = color else { continue };
 
-        rules.push((regexes.clone(), color, priority));
+        rules.push((rule_name, regexes.clone(), color, priority));
     }
 
     for line_index in 0..buffer.line_count() {
         let Some(line) = buffer.line(line_index) else {
             break;
         };
-        for (regexes, color, priority) in &rules {
+        for (rule_name, regexes, color, priority) in &rules {
             for regex in regexes {
                 for capture in regex.captures_iter(&line) {
                     let matchh = capture
@@ -76,6 +78,7 @@ pub fn regex_syntax_highlight(
                             },
                             priority: priority.unwrap_or(DEFAULT_PRIORITY),
                         },
+                        rule: rule_name.to_string(),
                     });
                 }
             }
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+/// How a buffer appears to be indented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DetectedIndentation {
+    pub with_tabs: bool,
+    /// The width of an indentation level, if indented with spaces.
+    pub size: Option<i32>,
+}
+
+/// Lines sampled from the start of buffers.
+const SAMPLE_LINE_COUNT: usize = 1000;
+
+/// Detects the indentation of `lines` from their leading whitespace: tabs
+/// if more lines start with a tab than with spaces, otherwise the most common
+/// increase of indentation between consecutive lines. `None` when too few
+/// lines are indented to tell.
+pub fn detect_indentation(lines: &[String]) -> Option<DetectedIndentation> {
+    let mut tab_line_count = 0;
+    let mut space_line_count = 0;
+    let mut increase_counts: HashMap<usize, usize> = HashMap::new();
+    let mut previous_width = 0;
+
+    for line in lines.iter().take(SAMPLE_LINE_COUNT) {
+        let content = line.trim_start();
+        // Blank lines and continuations of block comments (` * ...`) don't
+        // tell the indentation.
+        if content.is_empty() || content.starts_with('*') {
+            continue;
+        }
+        let indentation = &line[..line.len() - content.len()];
+
+        if indentation.starts_with('\t') {
+            tab_line_count += 1;
+            continue;
+        }
+        if indentation.contains('\t') {
+            continue;
+        }
+        let width = indentation.len();
+        if width > 0 {
+            space_line_count += 1;
+        }
+        if width > previous_width {
+            *increase_counts.entry(width - previous_width).or_default() += 1;
+        }
+        previous_width = width;
+    }
+
+    if tab_line_count == 0 && space_line_count == 0 {
+        return None;
+    }
+    if tab_line_count > space_line_count {
+        return Some(DetectedIndentation {
+            with_tabs: true,
+            size: None,
+        });
+    }
+
+    // Ties go to the smaller width, which larger ones are often multiples of.
+    let size = increase_counts
+        .into_iter()
+        .filter(|&(increase, _)| (2..=8).contains(&increase))
+        .max_by_key(|&(increase, count)| (count, std::cmp::Reverse(increase)))
+        .map(|(increase, _)| increase as i32)?;
+    Some(DetectedIndentation {
+        with_tabs: false,
+        size: Some(size),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(content: &str) -> Option<DetectedIndentation> {
+        let lines = content.split('\n').map(str::to_string).collect::<Vec<_>>();
+        detect_indentation(&lines)
+    }
+
+    #[test]
+    fn detects_spaces_and_tabs() {
+        let two_spaces = "fn a() {\n  if b {\n    c();\n  }\n\n  d(\n    e,\n  );\n}";
+        assert_eq!(
+            detect(two_spaces),
+            Some(DetectedIndentation {
+                with_tabs: false,
+                size: Some(2)
+            })
+        );
+
+        let tabs = "fn a() {\n\tif b {\n\t\tc();\n\t}\n}";
+        assert_eq!(
+            detect(tabs),
+            Some(DetectedIndentation {
+                with_tabs: true,
+                size: None
+            })
+        );
+
+        assert_eq!(detect("a\nb\n\nc"), None);
+    }
+}
@@ -0,0 +1,132 @@
+use std::path::PathBuf;
+
+use crate::{selection::Selections, slotmap::Handle};
+
+use super::{Resources, TextBuffer};
+
+const MAX_JUMPS: usize = 100;
+
+#[derive(Clone)]
+pub struct Jump {
+    pub buffer: Handle<TextBuffer>,
+    /// Used to reopen the buffer if it was closed since.
+    pub path: Option<PathBuf>,
+    pub selections: Selections,
+}
+
+impl Jump {
+    fn is_same_location(&self, other: &Jump) -> bool {
+        self.buffer == other.buffer
+            && self.selections.primary().cursor == other.selections.primary().cursor
+    }
+}
+
+/// Identifies the selections of a recorded jump in `TextBuffer::jumps`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct JumpId(u64);
+
+/// The jumps of the editor, across all of its buffers. Since the editor shows
+/// one view at a time, there is a single list, which jumping back and forth
+/// goes through whichever view is active.
+///
+/// While their buffer is open, the selections of the jumps are kept in it, so
+/// that they are adjusted on edits like the selections of views.
+#[derive(Default)]
+pub struct JumpList {
+    /// The jumps with the selections they had when they were recorded, or when
+    /// their buffer was closed.
+    jumps: Vec<(JumpId, Jump)>,
+    /// Index of the jump that `back` returns to, plus one. It is equal to the
+    /// length of `jumps` unless going back through the list.
+    index: usize,
+    next_id: u64,
+    /// The selections at the time of the last `selections-modified`, to tell
+    /// how far they moved since.
+    pub last_seen_selections: Option<Selections>,
+}
+
+impl JumpList {
+    /// Records the location being jumped away from. Jumps that could be
+    /// reached with `forward` are forgotten.
+    pub fn record(&mut self, jump: Jump, resources: &mut Resources) {
+        for (id, forgotten) in self.jumps.split_off(self.index) {
+            forget(id, &forgotten, resources);
+        }
+        let is_last = self.jumps.last().is_some_and(|(id, last)| {
+            with_current_selections(*id, last, resources).is_same_location(&jump)
+        });
+        if !is_last {
+            let id = JumpId(self.next_id);
+            self.next_id += 1;
+            if resources.buffers.contains(jump.buffer) {
+                let buffer = resources.buffers.get_mut(jump.buffer);
+                buffer.jumps.insert(id, jump.selections.clone());
+            }
+            self.jumps.push((id, jump));
+        }
+        if self.jumps.len() > MAX_JUMPS {
+            let (id, forgotten) = self.jumps.remove(0);
+            forget(id, &forgotten, resources);
+        }
+        self.index = self.jumps.len();
+    }
+
+    /// Returns the previous jump, if any. `current` is kept around so that
+    /// `forward` can come back to it.
+    pub fn back(&mut self, current: Jump, resources: &mut Resources) -> Option<Jump> {
+        if self.index == self.jumps.len() {
+            self.record(current, resources);
+            self.index = self.jumps.len() - 1;
+        }
+        if self.index == 0 {
+            return None;
+        }
+        self.index -= 1;
+        let (id, jump) = &self.jumps[self.index];
+        Some(with_current_selections(*id, jump, resources))
+    }
+
+    pub fn forward(&mut self, resources: &Resources) -> Option<Jump> {
+        if self.index + 1 >= self.jumps.len() {
+            return None;
+        }
+        self.index += 1;
+        let (id, jump) = &self.jumps[self.index];
+        Some(with_current_selections(*id, jump, resources))
+    }
+
+    /// Saves the selections of the jumps into a buffer that is being closed,
+    /// to restore them once it is reopened.
+    pub fn save_closed_buffer_jumps(
+        &mut self,
+        buffer_handle: Handle<TextBuffer>,
+        buffer: &TextBuffer,
+    ) {
+        for (id, jump) in self.jumps.iter_mut() {
+            if jump.buffer == buffer_handle
+                && let Some(selections) = buffer.jumps.get(id)
+            {
+                jump.selections = selections.clone();
+            }
+        }
+    }
+}
+
+/// The jump with the selections its buffer kept up to date, if it is open.
+fn with_current_selections(id: JumpId, jump: &Jump, resources: &Resources) -> Jump {
+    let selections = resources
+        .buffers
+        .contains(jump.buffer)
+        .then(|| resources.buffers.get(jump.buffer).jumps.get(&id))
+        .flatten();
+    Jump {
+        selections: selections.unwrap_or(&jump.selections).clone(),
+        ..jump.clone()
+    }
+}
+
+fn forget(id: JumpId, jump: &Jump, resources: &mut Resources) {
+    if resources.buffers.contains(jump.buffer) {
+        resources.buffers.get_mut(jump.buffer).jumps.remove(&id);
+    }
+}
@@ -4,11 +4,12 @@ use std::{
 };
 
 use crate::{
-    config::Config,
+    config::{Config, ConfigError},
     input::Input,
     panels::{
         FocusedPanel,
         file_picker::FilePickerState,
+        marks_picker::MarksPickerState,
         modeline::{Align, ModelineInfo, ModelineState},
     },
     slotmap::Handle,
@@ -22,15 +23,35 @@ pub use text_buffer_history::TextBufferHistory;
 mod text_buffer;
 pub use text_buffer::TextBuffer;
 
+mod buffer_settings;
+pub use buffer_settings::{BufferSettings, Charset};
+
+mod indent_detection;
+pub use indent_detection::{DetectedIndentation, detect_indentation};
+
 mod view;
 pub use view::View;
 
 mod highlight;
 pub use highlight::{Highlight, regex_syntax_highlight};
 
+mod brackets;
+pub use brackets::{find_bracket_pair, find_enclosing_bracket_pair};
+
+mod words;
+pub use words::{WordKind, WordMotion, word_motion_selection};
+
 mod register;
 pub use register::Register;
 
+mod search;
+pub use search::{
+    InteractiveReplace, Search, SearchOrigin, find_search_match, search_matches_in_rows,
+};
+
+mod jump_list;
+pub use jump_list::{Jump, JumpId, JumpList};
+
 mod resources;
 pub use resources::Resources;
 
@@ -44,10 +65,23 @@ pub struct State {
     pub edit_histories: HashMap<Handle<TextBuffer>, TextBufferHistory>,
     pub suggestions: Suggestions,
     pub register: Register,
+    pub search: Search,
+    pub interactive_replace: Option<InteractiveReplace>,
+    pub jump_list: JumpList,
+    /// Named selections saved with `mark-save`, shared by all buffers. Like
+    /// jumps, they keep the path of their buffer to reopen it once closed.
+    /// While the buffer is open, their selections are kept up to date in
+    /// `TextBuffer::marks`, see `mark`.
+    pub marks: HashMap<String, Jump>,
     pub config: Config,
+    /// The errors of the last config modules that failed to load.
+    pub config_errors: Vec<ConfigError>,
+    /// The buffer that `config-errors` shows the errors in.
+    pub config_errors_buffer: Option<Handle<TextBuffer>>,
     pub modeline: ModelineState,
     pub hover_info: Option<String>,
     pub file_picker: FilePickerState,
+    pub marks_picker: MarksPickerState,
     pub focused_panel: FocusedPanel,
     pub quit_requested: bool,
     pub viewport_size: Size,
@@ -63,7 +97,9 @@ pub struct State {
 impl State {
     pub fn focused_view(&self) -> Option<Handle<View>> {
         match self.focused_panel {
-            FocusedPanel::Editor | FocusedPanel::Warpdrive => self.active_editor_view,
+            FocusedPanel::Editor | FocusedPanel::MarksPicker | FocusedPanel::Warpdrive => {
+                self.active_editor_view
+            }
             FocusedPanel::Modeline(view) => Some(view),
             FocusedPanel::FilePicker(view) => Some(view),
         }
@@ -73,7 +109,7 @@ impl State {
         let (view_handle, panel_rect) = match self.focused_panel {
             FocusedPanel::Modeline(handle) => (Some(handle), self.modeline_rect),
             FocusedPanel::FilePicker(handle) => (Some(handle), self.file_picker_rect),
-            FocusedPanel::Editor | FocusedPanel::Warpdrive => {
+            FocusedPanel::Editor | FocusedPanel::MarksPicker | FocusedPanel::Warpdrive => {
                 (self.active_editor_view, self.editor_rect)
             }
         };
@@ -91,6 +127,63 @@ impl State {
         Rect::with_position_and_size(top_left, panel_rect.size())
     }
 
+    /// Makes `view_handle` the active editor view.
+    pub fn set_active_editor_view(&mut self, view_handle: Handle<View>, resources: &Resources) {
+        self.active_editor_view = Some(view_handle);
+
+        // Moves are tracked from the selections the view is entered with.
+        let buffer = resources
+            .buffers
+            .get(resources.views.get(view_handle).buffer);
+        self.jump_list.last_seen_selections = buffer.view_selections(view_handle).cloned();
+    }
+
+    /// The location of the active editor view, as it would be recorded in its
+    /// jump list.
+    pub fn active_editor_jump(&self, resources: &Resources) -> Option<Jump> {
+        let view_handle = self.active_editor_view?;
+        let buffer_handle = resources.views.get(view_handle).buffer;
+        let buffer = resources.buffers.get(buffer_handle);
+        Some(Jump {
+            buffer: buffer_handle,
+            path: buffer.path().map(Path::to_path_buf),
+            selections: buffer.view_selections(view_handle)?.clone(),
+        })
+    }
+
+    /// The mark `name`, with the selections its buffer kept up to date if it
+    /// is open.
+    pub fn mark(&self, name: &str, resources: &Resources) -> Option<Jump> {
+        let mark = self.marks.get(name)?;
+        let selections = resources
+            .buffers
+            .contains(mark.buffer)
+            .then(|| resources.buffers.get(mark.buffer).marks.get(name))
+            .flatten();
+        Some(Jump {
+            selections: selections.unwrap_or(&mark.selections).clone(),
+            ..mark.clone()
+        })
+    }
+
+    /// Saves the selections of the jumps and marks into a buffer that is being
+    /// closed, to restore them once it is reopened.
+    pub fn save_closed_buffer_selections(
+        &mut self,
+        buffer_handle: Handle<TextBuffer>,
+        buffer: &TextBuffer,
+    ) {
+        self.jump_list
+            .save_closed_buffer_jumps(buffer_handle, buffer);
+        for (name, selections) in &buffer.marks {
+            if let Some(mark) = self.marks.get_mut(name)
+                && mark.buffer == buffer_handle
+            {
+                mark.selections = selections.clone();
+            }
+        }
+    }
+
     pub fn active_editor_buffer(&self, resources: &Resources) -> Option<Handle<TextBuffer>> {
         Some(resources.views.get(self.active_editor_view?).buffer)
     }
@@ -133,6 +226,19 @@ impl State {
             };
             infos.push(path_info);
 
+            // Indentation info
+            let editor = buffer.editor_config(&self.config);
+            let indentation_text = if editor.indent_with_tabs {
+                format!("tabs:{}", editor.tab_width())
+            } else {
+                format!("spaces:{}", editor.indent_size)
+            };
+            infos.push(ModelineInfo {
+                text: indentation_text,
+                style: Style::default(),
+                align: Align::Right,
+            });
+
             // Cursor info
             let sels = buffer
                 .view_selections(
@@ -0,0 +1,162 @@
+use std::{collections::VecDeque, ops::Range};
+
+use regex::{Regex, RegexBuilder};
+
+use crate::{
+    position::{Column, Position, Row},
+    selection::{Selection, Selections},
+    slotmap::Handle,
+};
+
+use super::{TextBuffer, View};
+
+#[derive(Default)]
+pub struct Search {
+    /// The regex of the current search, used for `search-next` and to
+    /// highlight matches.
+    pub regex: Option<Regex>,
+    pub reversed: bool,
+    pub case_insensitive: bool,
+    pub literal: bool,
+    /// What to restore if the search prompt is cancelled.
+    pub origin: Option<SearchOrigin>,
+}
+
+pub struct SearchOrigin {
+    pub selections: Selections,
+    pub regex: Option<Regex>,
+}
+
+impl Search {
+    pub fn build_regex(&self, pattern: &str) -> Result<Regex, String> {
+        let pattern = if self.literal {
+            regex::escape(pattern)
+        } else {
+            pattern.to_string()
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(self.case_insensitive)
+            .multi_line(true)
+            .build()
+            .map_err(|err| err.to_string())
+    }
+}
+
+pub struct SearchMatch {
+    pub selection: Selection,
+    pub wrapped: bool,
+}
+
+/// Finds the first match starting after `from` (or before it when `backward`),
+/// wrapping around the buffer if needed. When `inclusive`, a match starting
+/// exactly on `from` is accepted.
+pub fn find_search_match(
+    buffer: &TextBuffer,
+    regex: &Regex,
+    from: Position,
+    backward: bool,
+    inclusive: bool,
+) -> Option<SearchMatch> {
+    let content = buffer.content_to_string();
+    let from_idx = buffer.map_position_to_byte_index(from).unwrap_or(0);
+
+    let matches = regex
+        .find_iter(&content)
+        .map(|m| m.range())
+        .collect::<Vec<_>>();
+    let is_candidate = |start: usize| match (backward, inclusive) {
+        (false, true) => start >= from_idx,
+        (false, false) => start > from_idx,
+        (true, true) => start <= from_idx,
+        (true, false) => start < from_idx,
+    };
+
+    let (range, wrapped) = if backward {
+        match matches.iter().rev().find(|r| is_candidate(r.start)) {
+            Some(range) => (range, false),
+            None => (matches.last()?, true),
+        }
+    } else {
+        match matches.iter().find(|r| is_candidate(r.start)) {
+            Some(range) => (range, false),
+            None => (matches.first()?, true),
+        }
+    };
+
+    let start = buffer.map_byte_index_to_position(range.start, false)?;
+    let end = if range.is_empty() {
+        start
+    } else {
+        buffer.map_byte_index_to_position(range.end, true)?
+    };
+    let selection = Selection::new().with_anchor(start).with_cursor(end);
+    Some(SearchMatch { selection, wrapped })
+}
+
+/// Finds the matches within the given rows, split per line. Returned as
+/// `(row, start column, end column)`, with an inclusive end.
+pub fn search_matches_in_rows(
+    buffer: &TextBuffer,
+    regex: &Regex,
+    first_row: Row,
+    last_row: Row,
+) -> Vec<(Row, Column, Column)> {
+    let first_row = first_row.max(0);
+    let last_row = last_row.min(buffer.last_row());
+    if first_row > last_row {
+        return Vec::new();
+    }
+
+    let mut text = String::new();
+    let mut line_starts = Vec::new();
+    for row in first_row..=last_row {
+        if row != first_row {
+            text.push('\n');
+        }
+        line_starts.push(text.len());
+        text.push_str(buffer.line(row).unwrap_or_default());
+    }
+
+    let to_row_column = |idx: usize| {
+        let line_idx = line_starts.partition_point(|&start| start <= idx) - 1;
+        let line_start = line_starts[line_idx];
+        let column = text[line_start..idx].chars().count();
+        (line_idx, column as Column)
+    };
+
+    let mut matches = Vec::new();
+    for m in regex.find_iter(&text) {
+        if m.is_empty() {
+            continue;
+        }
+        let (start_line, start_column) = to_row_column(m.start());
+        let (end_line, end_column) = to_row_column(m.end() - 1);
+        for line_idx in start_line..=end_line {
+            let from = if line_idx == start_line {
+                start_column
+            } else {
+                0
+            };
+            let row = first_row + line_idx as Row;
+            let to = if line_idx == end_line {
+                end_column
+            } else {
+                // Up to the line break
+                buffer.line_char_count(row).unwrap_or_default()
+            };
+            matches.push((row, from, to));
+        }
+    }
+    matches
+}
+
+/// A `replace-regex --interactive` in progress.
+pub struct InteractiveReplace {
+    pub view: Handle<View>,
+    /// The matches that are yet to be accepted or skipped, as byte ranges into
+    /// the buffer's content, along with their replacement.
+    pub pending: VecDeque<(Range<usize>, String)>,
+    pub replaced_count: usize,
+    /// The mode to go back to once done.
+    pub previous_mode: String,
+}
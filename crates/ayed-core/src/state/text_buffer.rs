@@ -5,7 +5,7 @@ use std::{
 };
 
 use crate::{
-    config::Config,
+    config::{Config, EditorConfig},
     position::{Column, Offset, Position, Row},
     selection::{Selection, Selections},
     slotmap::Handle,
@@ -15,7 +15,7 @@ use crate::{
     },
 };
 
-use super::View;
+use super::{BufferSettings, Charset, JumpId, View};
 
 // #1. There should always be at least one line. A line is a String in the lines vector.
 // #2. The line terminators are not part of the content, they are implied for the
@@ -28,6 +28,18 @@ use super::View;
 pub struct TextBuffer {
     pub lines: Vec<String>,
     pub selections: HashMap<Handle<View>, Selections>,
+    /// Closing characters inserted by auto-pairs, which typing them again
+    /// steps over. Kept as selections so that they are adjusted on edits too.
+    pub auto_pair_closers: Vec<Selection>,
+    /// Selections of the jump list's jumps into this buffer, adjusted on edits
+    /// like the selections of views.
+    pub jumps: HashMap<JumpId, Selections>,
+    /// Selections of the marks saved in this buffer, by name, adjusted on edits
+    /// like the selections of views.
+    pub marks: HashMap<String, Selections>,
+    /// Settings of this buffer overriding the editor config, like those of
+    /// `.editorconfig` files.
+    pub settings: BufferSettings,
     pub path: Option<PathBuf>,
     pub dirty: Cell<bool>, // Using Cell just to allow write_atomic and write_to_atomic to be non mut.
     pub history_dirty: Cell<bool>, // Used to prevent saving changes to the undo/redo stack when there is none.
@@ -41,6 +53,10 @@ impl TextBuffer {
         Self {
             lines: vec![String::new()], // Uphold #1.
             selections: Default::default(),
+            auto_pair_closers: Default::default(),
+            jumps: Default::default(),
+            marks: Default::default(),
+            settings: Default::default(),
             path: None,
             dirty: Default::default(),
             history_dirty: Default::default(),
@@ -56,6 +72,10 @@ impl TextBuffer {
         Ok(Self {
             lines,
             selections: Default::default(),
+            auto_pair_closers: Default::default(),
+            jumps: Default::default(),
+            marks: Default::default(),
+            settings: Default::default(),
             path: Some(path.to_path_buf()),
             dirty: Default::default(),
             history_dirty: Default::default(),
@@ -106,9 +126,24 @@ impl TextBuffer {
     }
 
     fn write_content<W: std::io::Write>(&self, w: &mut W) -> Result<(), std::io::Error> {
+        const BOM: char = '\u{feff}';
+        let line_ending = self.settings.line_ending.map(|line_ending| line_ending.as_str());
         for (i, line) in self.lines.iter().enumerate() {
-            if i != 0 {
-                w.write_all(&[b'\n'])?;
+            let mut line = line.as_str();
+            if i == 0 {
+                match self.settings.charset {
+                    Some(Charset::Utf8) => line = line.trim_start_matches(BOM),
+                    Some(Charset::Utf8Bom) if !line.starts_with(BOM) => {
+                        w.write_all(BOM.to_string().as_bytes())?;
+                    }
+                    _ => (),
+                }
+            } else {
+                w.write_all(line_ending.unwrap_or("\n").as_bytes())?;
+            }
+            // Lines of files with CRLF line endings end with a carriage return.
+            if line_ending.is_some() {
+                line = line.strip_suffix('\r').unwrap_or(line);
             }
             w.write_all(line.as_bytes())?;
         }
@@ -155,20 +190,26 @@ impl TextBuffer {
         self.path = path.into();
     }
 
+    /// The editor config with the settings of this buffer applied.
+    pub fn editor_config(&self, config: &Config) -> EditorConfig {
+        self.settings.apply(config.get_editor())
+    }
+
     // I'd document this properly if I knew I to put words together to describe it
     // but basically this is to handle how to display tabs.
     // All code that wants to display a line should use this.
     pub fn logical_line(&self, row_index: Row, config: &Config) -> Option<String> {
         self.lines
             .get(row_index as usize)
-            .map(|s| s.replace('\t', &" ".repeat(config.get_editor().indent_size as usize)))
+            .map(|s| s.replace('\t', &" ".repeat(self.editor_config(config).tab_width() as usize)))
     }
 
     pub fn logical_line_char_count(&self, row: Row, config: &Config) -> Option<i32> {
         let line = self.line(row)?;
+        let tab_width = self.editor_config(config).tab_width();
         let mut logical_char_count = 0;
         for ch in line.chars() {
-            let count = logical_char_char_count(ch, config);
+            let count = logical_char_char_count(ch, tab_width);
             logical_char_count += count;
         }
         Some(logical_char_count)
@@ -182,10 +223,11 @@ impl TextBuffer {
         let Some(line) = self.line(position.row) else {
             return position;
         };
+        let tab_width = self.editor_config(config).tab_width();
         let mut logical_column = 0;
         let chars = line.chars().chain(Some('\n'));
         for ch in chars.take(position.column as usize) {
-            let count = logical_char_char_count(ch, config);
+            let count = logical_char_char_count(ch, tab_width);
             logical_column += count;
         }
         position.with_column(logical_column)
@@ -199,10 +241,11 @@ impl TextBuffer {
         let Some(line) = self.line(logpos.row) else {
             return logpos;
         };
+        let tab_width = self.editor_config(config).tab_width();
         let mut logical_char_count = 0;
         let mut char_count: i32 = 0;
         for ch in line.chars() {
-            let count = logical_char_char_count(ch, config);
+            let count = logical_char_char_count(ch, tab_width);
             logical_char_count += count;
             if logical_char_count > logpos.column {
                 break;
@@ -227,7 +270,11 @@ impl TextBuffer {
             }
             let mut byte_idx = idx - line_start_bytes;
             if byte_index_end {
+                // The last byte of a multibyte char maps to that char.
                 byte_idx = byte_idx.saturating_sub(1);
+                while !line.is_char_boundary(byte_idx) {
+                    byte_idx -= 1;
+                }
             }
             let column = byte_index_to_char_index(line, byte_idx).unwrap();
             return Some(Position::new(column as Column, row));
@@ -490,6 +537,44 @@ impl TextBuffer {
         Ok(())
     }
 
+    /// Replaces the text of each of the view's selections by the text with the same index,
+    /// `None` leaving the selection as is. The selections then cover their new text.
+    pub fn replace_view_selections_text(
+        &mut self,
+        view: Handle<View>,
+        texts: &[Option<String>],
+    ) -> Result<(), String> {
+        let selections = self
+            .view_selections(view)
+            .ok_or_else(|| String::from("no selections for view"))?;
+
+        // Going from the end of the buffer, so that the selections yet to be replaced don't move.
+        let mut indices = (0..selections.count()).collect::<Vec<_>>();
+        indices.sort_by_key(|&i| std::cmp::Reverse(selections.get(i).unwrap().start()));
+
+        for i in indices {
+            let Some(Some(text)) = texts.get(i) else {
+                continue;
+            };
+            let selection = self.view_selections(view).unwrap().get(i).unwrap();
+            let start = selection.start();
+            self.delete_selection(&selection)?;
+            let new_selection = if text.is_empty() {
+                Selection::with_position(start)
+            } else {
+                self.insert_str_at(start, text)?
+            };
+            let new_selection = if selection.is_forward() {
+                new_selection
+            } else {
+                new_selection.flipped()
+            };
+            *self.view_selections_mut(view).unwrap().get_mut(i).unwrap() = new_selection;
+        }
+
+        Ok(())
+    }
+
     pub fn join_line_with_next(&mut self, row: Row) -> Result<(), String> {
         if row > self.last_row() {
             return Err(String::from("bad row"));
@@ -521,32 +606,26 @@ impl TextBuffer {
     }
 
     fn adjust_selections_after_insert_char(&mut self, inserted_at: Position) {
-        for selections in self.selections() {
-            for selection in selections.iter_mut() {
-                let cursor = Self::adjust_position_after_insert_char(selection.cursor, inserted_at);
-                let anchor = Self::adjust_position_after_insert_char(selection.anchor, inserted_at);
-                *selection = selection.with_anchor(anchor).with_cursor(cursor);
-            }
+        for selection in self.adjusted_selections() {
+            let cursor = Self::adjust_position_after_insert_char(selection.cursor, inserted_at);
+            let anchor = Self::adjust_position_after_insert_char(selection.anchor, inserted_at);
+            *selection = selection.with_anchor(anchor).with_cursor(cursor);
         }
     }
 
     fn adjust_selections_after_split_line(&mut self, split_at: Position) {
-        for selections in self.selections() {
-            for selection in selections.iter_mut() {
-                let cursor = Self::adjust_position_after_split_line(selection.cursor, split_at);
-                let anchor = Self::adjust_position_after_split_line(selection.anchor, split_at);
-                *selection = selection.with_anchor(anchor).with_cursor(cursor);
-            }
+        for selection in self.adjusted_selections() {
+            let cursor = Self::adjust_position_after_split_line(selection.cursor, split_at);
+            let anchor = Self::adjust_position_after_split_line(selection.anchor, split_at);
+            *selection = selection.with_anchor(anchor).with_cursor(cursor);
         }
     }
 
     fn adjust_selections_after_delete_at(&mut self, deleted_at: Position) {
-        for selections in self.selections() {
-            for selection in selections.iter_mut() {
-                let cursor = Self::adjust_position_after_delete_at(selection.cursor, deleted_at);
-                let anchor = Self::adjust_position_after_delete_at(selection.anchor, deleted_at);
-                *selection = selection.with_anchor(anchor).with_cursor(cursor);
-            }
+        for selection in self.adjusted_selections() {
+            let cursor = Self::adjust_position_after_delete_at(selection.cursor, deleted_at);
+            let anchor = Self::adjust_position_after_delete_at(selection.anchor, deleted_at);
+            *selection = selection.with_anchor(anchor).with_cursor(cursor);
         }
     }
 
@@ -555,25 +634,29 @@ impl TextBuffer {
         row: Row,
         original_line_char_count: usize,
     ) {
-        for selections in self.selections() {
-            for selection in selections.iter_mut() {
-                let cursor = Self::adjust_position_after_join_line_with_next(
-                    selection.cursor,
-                    row,
-                    original_line_char_count,
-                );
-                let anchor = Self::adjust_position_after_join_line_with_next(
-                    selection.anchor,
-                    row,
-                    original_line_char_count,
-                );
-                *selection = selection.with_anchor(anchor).with_cursor(cursor);
-            }
+        for selection in self.adjusted_selections() {
+            let cursor = Self::adjust_position_after_join_line_with_next(
+                selection.cursor,
+                row,
+                original_line_char_count,
+            );
+            let anchor = Self::adjust_position_after_join_line_with_next(
+                selection.anchor,
+                row,
+                original_line_char_count,
+            );
+            *selection = selection.with_anchor(anchor).with_cursor(cursor);
         }
     }
 
-    fn selections(&mut self) -> impl Iterator<Item = &mut Selections> {
-        self.selections.values_mut()
+    /// Every selection that must follow the content as it is edited.
+    fn adjusted_selections(&mut self) -> impl Iterator<Item = &mut Selection> {
+        self.selections
+            .values_mut()
+            .flat_map(Selections::iter_mut)
+            .chain(self.auto_pair_closers.iter_mut())
+            .chain(self.jumps.values_mut().flat_map(Selections::iter_mut))
+            .chain(self.marks.values_mut().flat_map(Selections::iter_mut))
     }
 
     fn adjust_position_after_insert_char(pos: Position, inserted_at: Position) -> Position {
@@ -631,9 +714,9 @@ impl TextBuffer {
     }
 }
 
-fn logical_char_char_count(ch: char, config: &Config) -> i32 {
+fn logical_char_char_count(ch: char, tab_width: i32) -> i32 {
     if ch == '\t' {
-        config.get_editor().indent_size
+        tab_width
     } else {
         1
     }
@@ -0,0 +1,463 @@
+use crate::{position::Position, selection::Selection, utils::string_utils::marks::is_mark};
+
+use super::TextBuffer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordKind {
+    /// Runs of word characters or of punctuation, following the spirit of
+    /// Unicode word segmentation: `don't`, `3.14` and runs of katakana are
+    /// single words, and each ideograph or hiragana is a word on its own.
+    Word,
+    /// Words further split on snake_case and camelCase boundaries.
+    Subword,
+    /// Runs of anything but whitespace.
+    BigWord,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordMotion {
+    /// Selects up to the start of the next word, like Kakoune's `w`.
+    NextStart,
+    /// Selects up to the end of the next word, like Kakoune's `e`.
+    NextEnd,
+    /// Selects back to the start of the previous word, like Kakoune's `b`.
+    PreviousStart,
+}
+
+/// The selection that a word motion from `cursor` selects, if the buffer
+/// doesn't end (or start) first. Line breaks between words are skipped.
+pub fn word_motion_selection(
+    buffer: &TextBuffer,
+    cursor: Position,
+    kind: WordKind,
+    motion: WordMotion,
+) -> Option<Selection> {
+    let chars = BufferChars { buffer, kind };
+    char_at(buffer, cursor)?;
+
+    let (begin, end) = match motion {
+        WordMotion::NextStart | WordMotion::NextEnd => {
+            let mut begin = cursor;
+            if let Some(next) = chars.next(begin)
+                && chars.is_boundary(begin, next)
+            {
+                begin = next;
+            }
+            while char_at(buffer, begin)? == '\n' {
+                begin = chars.next(begin)?;
+            }
+
+            let mut end = begin;
+            if motion == WordMotion::NextStart {
+                if !chars.is_blank(end) {
+                    end = chars.skip_word_forward(end);
+                }
+                while let Some(next) = chars.next(end)
+                    && chars.is_blank(next)
+                {
+                    end = next;
+                }
+            } else {
+                while chars.is_blank(end)
+                    && let Some(next) = chars.next(end)
+                {
+                    end = next;
+                }
+                end = chars.skip_word_forward(end);
+            }
+            (begin, end)
+        }
+        WordMotion::PreviousStart => {
+            let mut begin = cursor;
+            if let Some(previous) = chars.previous(begin)
+                && chars.is_boundary(previous, begin)
+            {
+                begin = previous;
+            }
+            while char_at(buffer, begin)? == '\n' {
+                begin = chars.previous(begin)?;
+            }
+
+            // Unlike forward motions, line breaks before the previous word are
+            // skipped as well, so that it doesn't stop at the end of a line.
+            let mut end = begin;
+            while (chars.is_blank(end) || char_at(buffer, end) == Some('\n'))
+                && let Some(previous) = chars.previous(end)
+            {
+                end = previous;
+            }
+            while let Some(previous) = chars.previous(end)
+                && !chars.is_boundary(previous, end)
+            {
+                end = previous;
+            }
+            (begin, end)
+        }
+    };
+
+    Some(Selection::new().with_anchor(begin).with_cursor(end))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Category {
+    Blank,
+    LineBreak,
+    Word,
+    Punctuation,
+}
+
+struct BufferChars<'a> {
+    buffer: &'a TextBuffer,
+    kind: WordKind,
+}
+
+impl BufferChars<'_> {
+    fn next(&self, position: Position) -> Option<Position> {
+        let line_char_count = self.buffer.line_char_count(position.row)?;
+        let next = if position.column < line_char_count {
+            position.offset((1, 0))
+        } else {
+            Position::new(0, position.row + 1)
+        };
+        char_at(self.buffer, next).map(|_| next)
+    }
+
+    fn previous(&self, position: Position) -> Option<Position> {
+        if position.column > 0 {
+            Some(position.offset((-1, 0)))
+        } else if position.row > 0 {
+            let row = position.row - 1;
+            Some(Position::new(self.buffer.line_char_count(row)?, row))
+        } else {
+            None
+        }
+    }
+
+    fn is_blank(&self, position: Position) -> bool {
+        char_at(self.buffer, position).is_some_and(|ch| self.category(ch) == Category::Blank)
+    }
+
+    /// The last position of the word at `position`.
+    fn skip_word_forward(&self, mut position: Position) -> Position {
+        while let Some(next) = self.next(position)
+            && !self.is_boundary(position, next)
+        {
+            position = next;
+        }
+        position
+    }
+
+    fn category(&self, ch: char) -> Category {
+        if ch == '\n' {
+            Category::LineBreak
+        } else if ch.is_whitespace() {
+            Category::Blank
+        } else if self.kind == WordKind::BigWord || is_word_char(ch) {
+            Category::Word
+        } else {
+            Category::Punctuation
+        }
+    }
+
+    /// Whether a word ends between the consecutive positions `a` and `b`.
+    fn is_boundary(&self, a: Position, b: Position) -> bool {
+        let (Some(a_ch), Some(b_ch)) = (char_at(self.buffer, a), char_at(self.buffer, b)) else {
+            return true;
+        };
+        let before = self
+            .previous(a)
+            .and_then(|position| char_at(self.buffer, position));
+        let after = self
+            .next(b)
+            .and_then(|position| char_at(self.buffer, position));
+        is_boundary(self.kind, before, a_ch, b_ch, after, |ch| self.category(ch))
+    }
+}
+
+fn is_boundary(
+    kind: WordKind,
+    before: Option<char>,
+    a: char,
+    b: char,
+    after: Option<char>,
+    category: impl Fn(char) -> Category,
+) -> bool {
+    let (a_category, b_category) = (category(a), category(b));
+    if kind == WordKind::BigWord {
+        return a_category != b_category;
+    }
+
+    // Combining marks (the accent of `e\u{301}`) belong to the character
+    // before them.
+    if is_mark(b) && matches!(a_category, Category::Word | Category::Punctuation) {
+        return false;
+    }
+    if a_category != b_category {
+        // Apostrophes within words (`don't`) and separators within numbers
+        // (`3.14`) don't end words.
+        let is_mid_letter = |ch: char| matches!(ch, '\'' | '’');
+        let is_mid_number = |ch: char| matches!(ch, '.' | ',');
+        let joins = |left: Option<char>, mid: char, right: Option<char>| {
+            let (Some(left), Some(right)) = (left, right) else {
+                return false;
+            };
+            (is_mid_letter(mid) && left.is_alphabetic() && right.is_alphabetic())
+                || (is_mid_number(mid) && left.is_numeric() && right.is_numeric())
+        };
+        return !(joins(Some(a), b, after) || joins(before, a, Some(b)));
+    }
+    if a_category != Category::Word {
+        return false;
+    }
+    // Katakana only join other katakana, through the marks after them.
+    let a_base = if is_mark(a) { before.unwrap_or(a) } else { a };
+    if is_katakana(a_base) || is_katakana(b) {
+        return !(is_katakana(a_base) && is_katakana(b));
+    }
+    if is_ideograph(a) || is_ideograph(b) {
+        return true;
+    }
+
+    // Sub-words end after underscores, before an uppercase letter following
+    // a lowercase letter or a digit, and before the last uppercase letter of
+    // an acronym followed by a word (`HTTPServer`).
+    kind == WordKind::Subword
+        && ((a == '_' && b != '_')
+            || (b.is_uppercase() && (a.is_lowercase() || a.is_numeric()))
+            || (a.is_uppercase() && b.is_uppercase() && after.is_some_and(char::is_lowercase)))
+}
+
+fn is_word_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_' || is_mark(ch)
+}
+
+/// Han ideographs and hiragana, which don't use spaces between words.
+fn is_ideograph(ch: char) -> bool {
+    matches!(ch,
+        '\u{3040}'..='\u{309F}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{20000}'..='\u{2A6DF}')
+}
+
+/// Katakana, including the halfwidth ones and the prolonged sound mark.
+fn is_katakana(ch: char) -> bool {
+    matches!(ch,
+        '\u{30A1}'..='\u{30FA}'
+        | '\u{30FC}'..='\u{30FF}'
+        | '\u{31F0}'..='\u{31FF}'
+        | '\u{FF66}'..='\u{FF9F}')
+}
+
+/// The character at `position`, where the end of a line is a line break,
+/// except for the last line.
+fn char_at(buffer: &TextBuffer, position: Position) -> Option<char> {
+    let line = buffer.line(position.row)?;
+    let line_char_count = buffer.line_char_count(position.row)?;
+    if position.column < 0 {
+        None
+    } else if position.column < line_char_count {
+        line.chars().nth(position.column as usize)
+    } else if position.column == line_char_count && position.row < buffer.last_row() {
+        Some('\n')
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::position::Column;
+
+    fn buffer(content: &str) -> TextBuffer {
+        let mut buffer = TextBuffer::new_empty();
+        buffer.lines = content.split('\n').map(str::to_string).collect();
+        buffer
+    }
+
+    fn motion(
+        content: &str,
+        column: Column,
+        kind: WordKind,
+        motion: WordMotion,
+    ) -> (Column, Column) {
+        let buffer = buffer(content);
+        let selection =
+            word_motion_selection(&buffer, Position::new(column, 0), kind, motion).unwrap();
+        (selection.anchor.column, selection.cursor.column)
+    }
+
+    #[test]
+    fn next_word_start() {
+        assert_eq!(
+            motion("foo bar", 0, WordKind::Word, WordMotion::NextStart),
+            (0, 3)
+        );
+        assert_eq!(
+            motion("foo bar", 2, WordKind::Word, WordMotion::NextStart),
+            (3, 3)
+        );
+        assert_eq!(
+            motion("foo.bar", 0, WordKind::Word, WordMotion::NextStart),
+            (0, 2)
+        );
+        assert_eq!(
+            motion("don't go", 0, WordKind::Word, WordMotion::NextStart),
+            (0, 5)
+        );
+        assert_eq!(
+            motion("x = 3.14;", 4, WordKind::Word, WordMotion::NextStart),
+            (4, 7)
+        );
+        assert_eq!(
+            motion("foo.bar baz", 0, WordKind::BigWord, WordMotion::NextStart),
+            (0, 7)
+        );
+    }
+
+    #[test]
+    fn next_word_end_and_previous_start() {
+        assert_eq!(
+            motion("foo  bar", 2, WordKind::Word, WordMotion::NextEnd),
+            (3, 7)
+        );
+        assert_eq!(
+            motion("foo  bar", 7, WordKind::Word, WordMotion::PreviousStart),
+            (7, 5)
+        );
+        assert_eq!(
+            motion("foo  bar", 5, WordKind::Word, WordMotion::PreviousStart),
+            (4, 0)
+        );
+    }
+
+    #[test]
+    fn subwords() {
+        let kind = WordKind::Subword;
+        assert_eq!(motion("fooBar", 0, kind, WordMotion::NextStart), (0, 2));
+        assert_eq!(motion("foo_bar", 0, kind, WordMotion::NextStart), (0, 3));
+        assert_eq!(motion("HTTPServer", 0, kind, WordMotion::NextEnd), (0, 3));
+        assert_eq!(
+            motion("parseXML", 8 - 1, kind, WordMotion::PreviousStart),
+            (7, 5)
+        );
+    }
+
+    #[test]
+    fn combining_marks() {
+        let kind = WordKind::Word;
+        assert_eq!(
+            motion("cafe\u{301} bar", 0, kind, WordMotion::NextStart),
+            (0, 5)
+        );
+        assert_eq!(
+            motion("cafe\u{301}.bar", 0, kind, WordMotion::NextEnd),
+            (0, 4)
+        );
+        assert_eq!(
+            motion(
+                "\u{915}\u{93F}\u{924}\u{93E}\u{92C} x",
+                0,
+                kind,
+                WordMotion::NextEnd
+            ),
+            (0, 4)
+        );
+    }
+
+    #[test]
+    fn katakana_runs() {
+        let kind = WordKind::Word;
+        // カタカナ and テスト, the prolonged sound mark of コーヒー included.
+        assert_eq!(
+            motion(
+                "\u{30AB}\u{30BF}\u{30AB}\u{30CA} x",
+                0,
+                kind,
+                WordMotion::NextEnd
+            ),
+            (0, 3)
+        );
+        assert_eq!(
+            motion(
+                "\u{30B3}\u{30FC}\u{30D2}\u{30FC}",
+                0,
+                kind,
+                WordMotion::NextEnd
+            ),
+            (0, 3)
+        );
+        // With a decomposed voiced sound mark, and halfwidth.
+        assert_eq!(
+            motion("\u{30AB}\u{3099}\u{30BF} x", 0, kind, WordMotion::NextEnd),
+            (0, 2)
+        );
+        assert_eq!(
+            motion(
+                "\u{FF83}\u{FF9E}\u{FF70}\u{FF80} x",
+                0,
+                kind,
+                WordMotion::NextEnd
+            ),
+            (0, 3)
+        );
+
+        // Katakana don't join letters, hiragana or ideographs.
+        assert_eq!(
+            motion(
+                "\u{30C6}\u{30B9}\u{30C8}abc",
+                0,
+                kind,
+                WordMotion::NextStart
+            ),
+            (0, 2)
+        );
+        assert_eq!(
+            motion(
+                "\u{30C6}\u{30B9}\u{30C8}\u{3092}",
+                0,
+                kind,
+                WordMotion::NextEnd
+            ),
+            (0, 2)
+        );
+        assert_eq!(
+            motion(
+                "\u{6F22}\u{5B57}\u{30C6}\u{30B9}\u{30C8}",
+                1,
+                kind,
+                WordMotion::NextStart
+            ),
+            (2, 4)
+        );
+        assert_eq!(
+            motion("\u{304B}\u{305F} x", 0, kind, WordMotion::NextEnd),
+            (1, 1)
+        );
+    }
+
+    #[test]
+    fn motions_cross_line_breaks() {
+        let buffer = buffer("foo\n  bar");
+        let selection = word_motion_selection(
+            &buffer,
+            Position::new(2, 0),
+            WordKind::Word,
+            WordMotion::NextStart,
+        )
+        .unwrap();
+        assert_eq!(selection.anchor, Position::new(0, 1));
+        assert_eq!(selection.cursor, Position::new(1, 1));
+
+        let selection = word_motion_selection(
+            &buffer,
+            Position::new(2, 1),
+            WordKind::Word,
+            WordMotion::PreviousStart,
+        )
+        .unwrap();
+        assert_eq!(selection.cursor, Position::new(0, 0));
+    }
+}
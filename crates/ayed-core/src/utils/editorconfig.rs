@@ -0,0 +1,284 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use regex::Regex;
+
+/// The EditorConfig properties that apply to the file at `path`, gathered
+/// from the `.editorconfig` files of its directory and its ancestors, up to
+/// the first one with `root = true`. Closer files and later sections take
+/// precedence. Property names and values are lowercase.
+pub fn editorconfig_properties(path: &Path) -> HashMap<String, String> {
+    let mut files = Vec::new();
+    for dir in path.ancestors().skip(1) {
+        let Ok(src) = std::fs::read_to_string(dir.join(".editorconfig")) else {
+            continue;
+        };
+        let file = EditorConfigFile::parse(&src);
+        let is_root = file.is_root;
+        files.push((dir.to_path_buf(), file));
+        if is_root {
+            break;
+        }
+    }
+
+    let mut properties = HashMap::new();
+    for (dir, file) in files.iter().rev() {
+        let Some(relative_path) = relative_glob_path(path, dir) else {
+            continue;
+        };
+        for section in &file.sections {
+            if section.glob.is_match(&relative_path) {
+                properties.extend(section.properties.clone());
+            }
+        }
+    }
+    properties.retain(|_, value| value != "unset");
+    properties
+}
+
+struct EditorConfigFile {
+    is_root: bool,
+    sections: Vec<Section>,
+}
+
+struct Section {
+    glob: Regex,
+    properties: Vec<(String, String)>,
+}
+
+impl EditorConfigFile {
+    fn parse(src: &str) -> Self {
+        let mut is_root = false;
+        let mut sections: Vec<Section> = Vec::new();
+        // Sections with globs that can't be parsed are skipped.
+        let mut in_invalid_section = false;
+
+        for line in src.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(['#', ';']) {
+                continue;
+            }
+
+            if let Some(glob) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                match glob_to_regex(glob) {
+                    Some(glob) => {
+                        sections.push(Section {
+                            glob,
+                            properties: Vec::new(),
+                        });
+                        in_invalid_section = false;
+                    }
+                    None => in_invalid_section = true,
+                }
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+            let value = value.trim().to_lowercase();
+            if in_invalid_section {
+                continue;
+            }
+            match sections.last_mut() {
+                Some(section) => section.properties.push((key, value)),
+                // Before the first section, only `root` is meaningful.
+                None if key == "root" => is_root = value == "true",
+                None => (),
+            }
+        }
+
+        Self { is_root, sections }
+    }
+}
+
+/// `path` relative to `dir`, with `/` as separator, which section globs are
+/// matched against.
+fn relative_glob_path(path: &Path, dir: &Path) -> Option<String> {
+    let relative_path: PathBuf = path.strip_prefix(dir).ok()?.into();
+    let parts = relative_path
+        .iter()
+        .map(|part| part.to_str())
+        .collect::<Option<Vec<_>>>()?;
+    Some(parts.join("/"))
+}
+
+/// Converts a section glob to a regex matching paths relative to the
+/// directory of the `.editorconfig` file. Globs without `/` match file names
+/// in any subdirectory.
+fn glob_to_regex(glob: &str) -> Option<Regex> {
+    let (glob, prefix) = if glob.contains('/') {
+        (glob.strip_prefix('/').unwrap_or(glob), "")
+    } else {
+        (glob, "(?:.*/)?")
+    };
+    let chars = glob.chars().collect::<Vec<_>>();
+    let pattern = format!("^{prefix}{}$", glob_chars_to_regex(&chars)?);
+    Regex::new(&pattern).ok()
+}
+
+fn glob_chars_to_regex(chars: &[char]) -> Option<String> {
+    let mut pattern = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                if chars.get(i + 2) == Some(&'/') {
+                    // `a/**/b` also matches `a/b`.
+                    pattern.push_str("(?:.*/)?");
+                    i += 3;
+                } else {
+                    pattern.push_str(".*");
+                    i += 2;
+                }
+                continue;
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            '[' => {
+                let Some(len) = chars[i..].iter().position(|&ch| ch == ']') else {
+                    pattern.push_str(r"\[");
+                    i += 1;
+                    continue;
+                };
+                let class = &chars[i + 1..i + len];
+                let (negated, class) = match class.split_first() {
+                    Some(('!', rest)) => (true, rest),
+                    _ => (false, class),
+                };
+                pattern.push('[');
+                if negated {
+                    pattern.push('^');
+                }
+                for &ch in class {
+                    if ch == '-' {
+                        pattern.push('-');
+                    } else {
+                        pattern.push_str(&regex::escape(&ch.to_string()));
+                    }
+                }
+                pattern.push(']');
+                i += len + 1;
+                continue;
+            }
+            '{' => {
+                let Some(len) = matching_brace_len(&chars[i..]) else {
+                    pattern.push_str(r"\{");
+                    i += 1;
+                    continue;
+                };
+                let inner = &chars[i + 1..i + len];
+                pattern.push_str(&braces_to_regex(inner)?);
+                i += len + 1;
+                continue;
+            }
+            '\\' if i + 1 < chars.len() => {
+                pattern.push_str(&regex::escape(&chars[i + 1].to_string()));
+                i += 2;
+                continue;
+            }
+            ch => pattern.push_str(&regex::escape(&ch.to_string())),
+        }
+        i += 1;
+    }
+    Some(pattern)
+}
+
+/// Converts the inside of `{a,b}` or `{1..3}` to a regex.
+fn braces_to_regex(inner: &[char]) -> Option<String> {
+    let text = inner.iter().collect::<String>();
+    if let Some((start, end)) = text.split_once("..")
+        && let (Ok(start), Ok(end)) = (start.parse::<i64>(), end.parse::<i64>())
+    {
+        let (start, end) = (start.min(end), start.max(end));
+        if end - start > 1000 {
+            return Some(r"-?\d+".to_string());
+        }
+        let numbers = (start..=end).map(|n| n.to_string()).collect::<Vec<_>>();
+        return Some(format!("(?:{})", numbers.join("|")));
+    }
+
+    let mut alternatives = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, &ch) in inner.iter().enumerate() {
+        match ch {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                alternatives.push(glob_chars_to_regex(&inner[start..i])?);
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    if alternatives.is_empty() {
+        // Not a list, like `{single}`, which is matched literally.
+        return Some(regex::escape(&format!("{{{text}}}")));
+    }
+    alternatives.push(glob_chars_to_regex(&inner[start..])?);
+    Some(format!("(?:{})", alternatives.join("|")))
+}
+
+/// The length from the `{` that `chars` starts with to its matching `}`.
+fn matching_brace_len(chars: &[char]) -> Option<usize> {
+    let mut depth = 0;
+    for (i, &ch) in chars.iter().enumerate() {
+        match ch {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => (),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(glob: &str, path: &str) -> bool {
+        glob_to_regex(glob).unwrap().is_match(path)
+    }
+
+    #[test]
+    fn globs() {
+        assert!(matches("*", "src/main.rs"));
+        assert!(matches("*.rs", "src/main.rs"));
+        assert!(!matches("*.rs", "src/main.py"));
+        assert!(matches("*.{js,ts}", "index.ts"));
+        assert!(matches("Makefile", "sub/Makefile"));
+        assert!(matches("/src/*.rs", "src/main.rs"));
+        assert!(!matches("/src/*.rs", "src/bin/main.rs"));
+        assert!(matches("src/**/*.rs", "src/main.rs"));
+        assert!(matches("src/**/*.rs", "src/bin/main.rs"));
+        assert!(matches("file[0-9].txt", "file3.txt"));
+        assert!(!matches("file[!0-9].txt", "file3.txt"));
+        assert!(matches("v{1..3}.md", "v2.md"));
+        assert!(!matches("v{1..3}.md", "v4.md"));
+    }
+
+    #[test]
+    fn sections_override_earlier_ones() {
+        let file = EditorConfigFile::parse(
+            "root = true\n\n[*]\nindent_style = space\nindent_size = 4\n\n# Go\n[*.go]\nindent_style = Tab\n",
+        );
+        assert!(file.is_root);
+        let mut properties = HashMap::new();
+        for section in &file.sections {
+            if section.glob.is_match("main.go") {
+                properties.extend(section.properties.clone());
+            }
+        }
+        assert_eq!(properties["indent_style"], "tab");
+        assert_eq!(properties["indent_size"], "4");
+    }
+}
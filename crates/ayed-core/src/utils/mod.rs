@@ -1,3 +1,4 @@
 pub mod render_utils;
 pub mod string_utils;
-pub mod path_ext;
\ No newline at end of file
+pub mod path_ext;
+pub mod editorconfig;
@@ -1,10 +1,10 @@
 use crate::{
     position::Position,
     ui::{
-        Size, Style,
+        Rect, Size, Style,
         ui_state::{StyledRegion, UiPanel},
     },
-    utils::string_utils::char_count,
+    utils::string_utils::{char_count, line_clamped_filled},
 };
 
 pub fn rectangle(position: Position, size: Size, style: Style) -> UiPanel {
@@ -97,3 +97,32 @@ pub fn separator_h(row: i32, content: &mut Vec<String>) {
     let last = chars.last().unwrap();
     *line = format!("{first}{sep}{last}");
 }
+
+/// A panel over `rect` with one of `rows` per line, each filled to the width
+/// of the panel with its style. The lines after the rows are blank.
+pub fn list_panel<'a>(
+    rect: Rect,
+    default_style: Style,
+    rows: impl IntoIterator<Item = (&'a str, Style)>,
+) -> UiPanel {
+    let size = rect.size();
+    let mut rows = rows.into_iter();
+    let mut content = Vec::new();
+    let mut spans = Vec::new();
+    for y in 0..size.row {
+        let (text, style) = rows.next().unwrap_or(("", default_style));
+        content.push(line_clamped_filled(text, 0, size.column as usize, ' '));
+        spans.push(StyledRegion {
+            from: Position::new(0, y),
+            to: Position::new(size.column, y),
+            style,
+            priority: 2,
+        });
+    }
+    UiPanel {
+        position: rect.top_left(),
+        size,
+        content,
+        spans,
+    }
+}
@@ -0,0 +1,197 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Case {
+    Lower,
+    Upper,
+    Swap,
+    Title,
+    Snake,
+    Camel,
+    Pascal,
+    Kebab,
+    Screaming,
+}
+
+impl Case {
+    pub fn parse(src: &str) -> Option<Self> {
+        let case = match src {
+            "lower" => Self::Lower,
+            "upper" => Self::Upper,
+            "swap" => Self::Swap,
+            "title" => Self::Title,
+            "snake" => Self::Snake,
+            "camel" => Self::Camel,
+            "pascal" => Self::Pascal,
+            "kebab" => Self::Kebab,
+            "screaming" => Self::Screaming,
+            _ => return None,
+        };
+        Some(case)
+    }
+}
+
+pub fn convert_case(s: &str, case: Case) -> String {
+    match case {
+        Case::Lower => s.to_lowercase(),
+        Case::Upper => s.to_uppercase(),
+        Case::Swap => s
+            .chars()
+            .flat_map(|ch| {
+                if ch.is_uppercase() {
+                    ch.to_lowercase().collect::<Vec<_>>()
+                } else {
+                    ch.to_uppercase().collect::<Vec<_>>()
+                }
+            })
+            .collect(),
+        Case::Title => {
+            let mut result = String::new();
+            let mut prev_is_alphanumeric = false;
+            for ch in s.chars() {
+                if prev_is_alphanumeric {
+                    result.extend(ch.to_lowercase());
+                } else {
+                    result.extend(ch.to_uppercase());
+                }
+                prev_is_alphanumeric = ch.is_alphanumeric();
+            }
+            result
+        }
+        Case::Snake | Case::Camel | Case::Pascal | Case::Kebab | Case::Screaming => {
+            convert_identifiers(s, case)
+        }
+    }
+}
+
+/// Converts every identifier-like run of `s` (alphanumerics, `_` and `-`),
+/// leaving the text around them as is.
+fn convert_identifiers(s: &str, case: Case) -> String {
+    let is_identifier_char = |ch: char| ch.is_alphanumeric() || ch == '_' || ch == '-';
+
+    let mut result = String::new();
+    let mut rest = s;
+    while !rest.is_empty() {
+        let run_len = rest
+            .find(|ch: char| is_identifier_char(ch) != rest.starts_with(is_identifier_char))
+            .unwrap_or(rest.len());
+        let (run, remaining) = rest.split_at(run_len);
+        if run.starts_with(is_identifier_char) {
+            result.push_str(&convert_identifier(run, case));
+        } else {
+            result.push_str(run);
+        }
+        rest = remaining;
+    }
+    result
+}
+
+fn convert_identifier(identifier: &str, case: Case) -> String {
+    let is_separator = |ch: char| ch == '_' || ch == '-';
+    let core = identifier.trim_matches(is_separator);
+    if core.is_empty() {
+        return identifier.to_string();
+    }
+    // Leading and trailing separators are meaningful (ex: `_unused`).
+    let prefix_len = identifier.len() - identifier.trim_start_matches(is_separator).len();
+    let prefix = &identifier[..prefix_len];
+    let suffix = &identifier[prefix.len() + core.len()..];
+
+    let words = split_words(core);
+    let mut converted = String::new();
+    for (i, word) in words.iter().enumerate() {
+        let lower = word.to_lowercase();
+        match case {
+            Case::Snake | Case::Kebab | Case::Screaming => {
+                if i != 0 {
+                    converted.push(if case == Case::Kebab { '-' } else { '_' });
+                }
+                if case == Case::Screaming {
+                    converted.push_str(&word.to_uppercase());
+                } else {
+                    converted.push_str(&lower);
+                }
+            }
+            Case::Camel if i == 0 => converted.push_str(&lower),
+            _ => converted.push_str(&capitalized(&lower)),
+        }
+    }
+
+    format!("{prefix}{converted}{suffix}")
+}
+
+fn capitalized(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Splits an identifier into its words, on separators and case changes.
+/// Acronyms are kept whole (`HTTPServer` gives `HTTP` and `Server`) and
+/// digits stick to the word they follow (`utf8Decoder` gives `utf8` and
+/// `Decoder`).
+pub fn split_words(identifier: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    for part in identifier.split(|ch: char| !ch.is_alphanumeric()) {
+        let chars = part.char_indices().collect::<Vec<_>>();
+        let mut word_start = 0;
+        for i in 1..chars.len() {
+            let (idx, ch) = chars[i];
+            let prev = chars[i - 1].1;
+            let next = chars.get(i + 1).map(|(_, ch)| *ch);
+            let is_boundary = ch.is_uppercase()
+                && (prev.is_lowercase()
+                    || prev.is_numeric()
+                    || (prev.is_uppercase() && next.is_some_and(char::is_lowercase)));
+            if is_boundary {
+                words.push(&part[word_start..idx]);
+                word_start = idx;
+            }
+        }
+        if word_start < part.len() {
+            words.push(&part[word_start..]);
+        }
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_words_handles_acronyms_and_digits() {
+        assert_eq!(split_words("HTTPServer"), ["HTTP", "Server"]);
+        assert_eq!(
+            split_words("parseXMLDocument"),
+            ["parse", "XML", "Document"]
+        );
+        assert_eq!(split_words("utf8Decoder"), ["utf8", "Decoder"]);
+        assert_eq!(
+            split_words("snake_case-and-kebab"),
+            ["snake", "case", "and", "kebab"]
+        );
+        assert_eq!(split_words("IO"), ["IO"]);
+    }
+
+    #[test]
+    fn convert_identifiers_in_text() {
+        assert_eq!(
+            convert_case("let fooBar = _baz_qux;", Case::Pascal),
+            "Let FooBar = _BazQux;"
+        );
+        assert_eq!(convert_case("HTTPServer", Case::Snake), "http_server");
+        assert_eq!(convert_case("http_server2", Case::Camel), "httpServer2");
+        assert_eq!(convert_case("myHTTPServer", Case::Kebab), "my-http-server");
+        assert_eq!(convert_case("maxValue", Case::Screaming), "MAX_VALUE");
+    }
+
+    #[test]
+    fn convert_letter_cases() {
+        assert_eq!(convert_case("Hello wORLD", Case::Swap), "hELLO World");
+        assert_eq!(
+            convert_case("hello wORLD-foo", Case::Title),
+            "Hello World-Foo"
+        );
+    }
+}
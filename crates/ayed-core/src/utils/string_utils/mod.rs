@@ -1,5 +1,7 @@
+pub mod case;
 pub mod grid_string_builder;
 pub mod line_builder;
+pub mod marks;
 pub mod ops;
 
 pub fn line_clamped_filled(line: &str, start: usize, char_count: usize, fill: char) -> String {
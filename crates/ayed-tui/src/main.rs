@@ -1,16 +1,34 @@
-use ayed_core::core::Core;
+use std::path::PathBuf;
+
+use ayed_core::core::{ConfigFiles, Core};
 
 mod tui;
 
 fn main() {
-    let mut core = Core::with_builtins();
+    let mut config_files = ConfigFiles::default();
+    let mut paths = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => {
+                let Some(path) = args.next() else {
+                    eprintln!("--config expects a path");
+                    std::process::exit(1);
+                };
+                config_files.extra_paths.push(PathBuf::from(path));
+            }
+            "--no-user-config" => config_files.no_user_config = true,
+            _ => paths.push(arg),
+        }
+    }
+
+    let mut core = Core::with_builtins_and_config_files(&config_files);
 
-    let mut any_path_specified = false;
-    for arg in std::env::args().skip(1) {
-        core.queue_command(format!("edit {arg}"));
-        any_path_specified = true;
+    for path in &paths {
+        core.queue_command(format!("edit {path}"));
     }
-    if !any_path_specified {
+    if paths.is_empty() {
         core.queue_command("edit --scratch".to_string());
     }
     core.tick();